@@ -35,11 +35,63 @@ pub fn bench_modarith(b: &mut Bencher) {
     });
 }
 
+/// Fresh ciphertext under the 2048 bit test modulus, i.e. a unit modulo `n^2`.
+fn ciphertext_2048() -> (BigInt, BigInt) {
+    let n = BigInt::from_str_radix(N2048, 10).unwrap();
+    let ek = EncryptionKey::from(&n);
+    let c: BigInt = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(10))).into();
+    (c, n)
+}
+
+pub fn bench_modinv_nn(b: &mut Bencher) {
+    let (c, n) = ciphertext_2048();
+    let nn: &BigInt = &(&n * &n);
+
+    b.iter(|| {
+        let _ = BigInt::mod_inv(&c, nn).unwrap();
+    });
+}
+
+pub fn bench_modpow_nn(b: &mut Bencher) {
+    let (c, n) = ciphertext_2048();
+    let nn: &BigInt = &(&n * &n);
+    let e: &BigInt = &(&n - BigInt::one());
+
+    b.iter(|| {
+        let _ = BigInt::mod_pow(&c, e, nn);
+    });
+}
+
+pub fn bench_gm_modpow(b: &mut Bencher) {
+    let p: &BigInt = &BigInt::from_str_radix(P2048, 10).unwrap();
+    let n: &BigInt = &BigInt::from_str_radix(N2048, 10).unwrap();
+    let nn: &BigInt = &(n * n);
+    let g: &BigInt = &(n + BigInt::one());
+
+    b.iter(|| {
+        let _ = BigInt::mod_pow(g, p, nn);
+    });
+}
+
+pub fn bench_gm_closed_form(b: &mut Bencher) {
+    let p: &BigInt = &BigInt::from_str_radix(P2048, 10).unwrap();
+    let n: &BigInt = &BigInt::from_str_radix(N2048, 10).unwrap();
+    let nn: &BigInt = &(n * n);
+
+    b.iter(|| {
+        let _ = (p * n + BigInt::one()) % nn;
+    });
+}
+
 benchmark_group!(
     group,
     self::bench_mul,
     self::bench_mulrem,
-    self::bench_modarith
+    self::bench_modarith,
+    self::bench_modinv_nn,
+    self::bench_modpow_nn,
+    self::bench_gm_modpow,
+    self::bench_gm_closed_form
 );
 
 benchmark_main!(group);
@@ -1,3 +1,5 @@
+use std::convert::TryFrom;
+
 use bencher::{benchmark_group, benchmark_main, Bencher};
 
 use kzen_paillier::*;
@@ -34,6 +36,71 @@ pub fn bench_decryption<KS: KeySize>(b: &mut Bencher) {
     });
 }
 
+pub fn bench_decryption_fresh_key<KS: KeySize>(b: &mut Bencher) {
+    let keypair = KS::keypair();
+    let (ek, _) = keypair.keys();
+    let minimal = MinimalDecryptionKey::from(&keypair);
+
+    let c = Paillier::encrypt(&ek, 10);
+
+    // build the key from its primes and decrypt once, to compare against bench_decryption
+    // and see the one-off cost of computing the cached values
+    b.iter(|| {
+        let dk = DecryptionKey::try_from(minimal.clone()).unwrap();
+        let _ = Paillier::decrypt(&dk, &c);
+    });
+}
+
+pub fn bench_decryption_sequential<KS: KeySize>(b: &mut Bencher) {
+    let keypair = KS::keypair();
+    let (ek, dk) = keypair.keys();
+
+    let cs: Vec<RawCiphertext> = (0..16)
+        .map(|i| Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(i))))
+        .collect();
+
+    b.iter(|| {
+        let _: Vec<RawPlaintext> = cs.iter().map(|c| Paillier::decrypt(&dk, c)).collect();
+    });
+}
+
+pub fn bench_decryption_batch<KS: KeySize>(b: &mut Bencher) {
+    let keypair = KS::keypair();
+    let (ek, dk) = keypair.keys();
+
+    let cs: Vec<RawCiphertext> = (0..16)
+        .map(|i| Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(i))))
+        .collect();
+
+    b.iter(|| {
+        let _ = Paillier::decrypt_batch(&dk, &cs);
+    });
+}
+
+fn bench_decryption_batch_chunked(b: &mut Bencher, chunk_size: usize) {
+    let (ek, dk) = KeySize2048::keypair().keys();
+
+    let cs: Vec<RawCiphertext> = (0..16)
+        .map(|i| Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(i))))
+        .collect();
+
+    b.iter(|| {
+        let _ = Paillier::decrypt_batch_with_chunk_size(&dk, &cs, chunk_size);
+    });
+}
+
+pub fn bench_decryption_batch_chunk_1(b: &mut Bencher) {
+    bench_decryption_batch_chunked(b, 1);
+}
+
+pub fn bench_decryption_batch_chunk_4(b: &mut Bencher) {
+    bench_decryption_batch_chunked(b, 4);
+}
+
+pub fn bench_decryption_batch_chunk_16(b: &mut Bencher) {
+    bench_decryption_batch_chunked(b, 16);
+}
+
 pub fn bench_rerandomisation<KS: KeySize>(b: &mut Bencher) {
     let keypair = KS::keypair();
     let ek = EncryptionKey::from(&keypair);
@@ -68,14 +135,90 @@ pub fn bench_multiplication<KS: KeySize>(b: &mut Bencher) {
     });
 }
 
+pub fn bench_negation<KS: KeySize>(b: &mut Bencher) {
+    let keypair = KS::keypair();
+    let ek = EncryptionKey::from(&keypair);
+
+    let c: BigInt = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(10))).into();
+
+    b.iter(|| {
+        let _ = Paillier::neg(&ek, RawCiphertext::from(&c));
+    });
+}
+
+fn bench_decryption_threshold(b: &mut Bencher, bit_length: usize, crt_threshold_bits: usize) {
+    let (ek, dk) = Paillier::keypair_with_modulus_size(bit_length).keys();
+
+    let c = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(10)));
+
+    b.iter(|| {
+        let _ = Paillier::decrypt_with_threshold(&dk, &c, crt_threshold_bits);
+    });
+}
+
+pub fn bench_decryption_direct_64(b: &mut Bencher) {
+    bench_decryption_threshold(b, 64, usize::MAX);
+}
+
+pub fn bench_decryption_crt_64(b: &mut Bencher) {
+    bench_decryption_threshold(b, 64, 0);
+}
+
+pub fn bench_decryption_direct_128(b: &mut Bencher) {
+    bench_decryption_threshold(b, 128, usize::MAX);
+}
+
+pub fn bench_decryption_crt_128(b: &mut Bencher) {
+    bench_decryption_threshold(b, 128, 0);
+}
+
+pub fn bench_decryption_direct_256(b: &mut Bencher) {
+    bench_decryption_threshold(b, 256, usize::MAX);
+}
+
+pub fn bench_decryption_crt_256(b: &mut Bencher) {
+    bench_decryption_threshold(b, 256, 0);
+}
+
+pub fn bench_decryption_direct_2048(b: &mut Bencher) {
+    bench_decryption_threshold(b, 2048, usize::MAX);
+}
+
+pub fn bench_decryption_crt_2048(b: &mut Bencher) {
+    bench_decryption_threshold(b, 2048, 0);
+}
+
+benchmark_group!(
+    decryption_threshold,
+    self::bench_decryption_direct_64,
+    self::bench_decryption_crt_64,
+    self::bench_decryption_direct_128,
+    self::bench_decryption_crt_128,
+    self::bench_decryption_direct_256,
+    self::bench_decryption_crt_256,
+    self::bench_decryption_direct_2048,
+    self::bench_decryption_crt_2048
+);
+
+benchmark_group!(
+    batch_chunk_size,
+    self::bench_decryption_batch_chunk_1,
+    self::bench_decryption_batch_chunk_4,
+    self::bench_decryption_batch_chunk_16
+);
+
 benchmark_group!(
     ks_2048,
     self::bench_encryption_ek<KeySize2048>,
     self::bench_encryption_dk<KeySize2048>,
     self::bench_decryption<KeySize2048>,
+    self::bench_decryption_fresh_key<KeySize2048>,
+    self::bench_decryption_sequential<KeySize2048>,
+    self::bench_decryption_batch<KeySize2048>,
     self::bench_rerandomisation<KeySize2048>,
     self::bench_addition<KeySize2048>,
-    self::bench_multiplication<KeySize2048>
+    self::bench_multiplication<KeySize2048>,
+    self::bench_negation<KeySize2048>
 );
 
 benchmark_group!(
@@ -83,9 +226,13 @@ benchmark_group!(
     self::bench_encryption_ek<KeySize4096>,
     self::bench_encryption_dk<KeySize4096>,
     self::bench_decryption<KeySize4096>,
+    self::bench_decryption_fresh_key<KeySize4096>,
+    self::bench_decryption_sequential<KeySize4096>,
+    self::bench_decryption_batch<KeySize4096>,
     self::bench_rerandomisation<KeySize4096>,
     self::bench_addition<KeySize4096>,
-    self::bench_multiplication<KeySize4096>
+    self::bench_multiplication<KeySize4096>,
+    self::bench_negation<KeySize4096>
 );
 
-benchmark_main!(ks_2048, ks_4096);
+benchmark_main!(ks_2048, ks_4096, decryption_threshold, batch_chunk_size);
@@ -1,25 +1,23 @@
-mod helpers;
-
-mod bench {
+use bencher::{benchmark_group, benchmark_main, Bencher};
 
-    use bencher::Bencher;
-    use helpers::*;
-    use kzen_paillier::*;
+use kzen_paillier::*;
 
-    pub fn bench_key_generation<KS: KeySize>(b: &mut Bencher) {
-        b.iter(|| {
-            Paillier::keypair_with_modulus_size(KS::size());
-        });
-    }
+mod helpers;
+use helpers::*;
 
-    benchmark_group!(
-        group,
-        self::bench_key_generation<KeySize512>,
-        self::bench_key_generation<KeySize1024>,
-        self::bench_key_generation<KeySize2048>,
-        self::bench_key_generation<KeySize3072>,
-        self::bench_key_generation<KeySize4096>
-    );
+pub fn bench_key_generation<KS: KeySize>(b: &mut Bencher) {
+    b.iter(|| {
+        Paillier::keypair_with_modulus_size(KS::size());
+    });
 }
 
-benchmark_main!(bench::group);
+benchmark_group!(
+    group,
+    self::bench_key_generation<KeySize512>,
+    self::bench_key_generation<KeySize1024>,
+    self::bench_key_generation<KeySize2048>,
+    self::bench_key_generation<KeySize3072>,
+    self::bench_key_generation<KeySize4096>
+);
+
+benchmark_main!(group);
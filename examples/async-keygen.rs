@@ -0,0 +1,21 @@
+use kzen_paillier::*;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    // generate the keypair on the blocking thread pool so that
+    // other tasks on this runtime keep making progress meanwhile
+    let keypair = Paillier::keypair_blocking_task(2048).await;
+
+    // the same can be achieved by hand for any other key generation method
+    let safe_keypair =
+        tokio::task::spawn_blocking(|| Paillier::keypair_safe_primes_with_modulus_size(1024))
+            .await
+            .unwrap();
+
+    for keypair in &[keypair, safe_keypair] {
+        let (ek, dk) = keypair.keys();
+        let c = Paillier::encrypt(&ek, 10);
+        let m = Paillier::decrypt(&dk, &c);
+        println!("decrypted value is {}", m);
+    }
+}
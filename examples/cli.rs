@@ -0,0 +1,113 @@
+//! Command line tool for trying out the scheme.
+//!
+//! Keys are stored as JSON files, plaintexts and ciphertexts are passed as decimal numbers:
+//!
+//! ```text
+//! cargo run --example cli -- keygen 2048 ek.json dk.json
+//! cargo run --example cli -- encrypt ek.json 20
+//! cargo run --example cli -- add ek.json <c1> <c2>
+//! cargo run --example cli -- decrypt dk.json <c>
+//! ```
+
+use std::fs;
+
+use kzen_paillier::*;
+
+const USAGE: &str = "usage:
+    cli keygen <bits> <ek-file> <dk-file>
+    cli encrypt <ek-file> <m>
+    cli decrypt <dk-file> <c>
+    cli add <ek-file> <c1> <c2>";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(output) => println!("{}", output),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<String, String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["keygen", bits, ek_file, dk_file] => {
+            let bits = bits
+                .parse()
+                .map_err(|_| format!("invalid bit length {}", bits))?;
+            let (ek, dk) = Paillier::keypair_with_modulus_size(bits).keys();
+            write_json(ek_file, &ek)?;
+            write_json(dk_file, &dk)?;
+            Ok(format!("wrote {} and {}", ek_file, dk_file))
+        }
+        ["encrypt", ek_file, m] => {
+            let ek: EncryptionKey = read_json(ek_file)?;
+            let m: RawPlaintext = m.parse().map_err(|e: Error| e.to_string())?;
+            let c = Paillier::encrypt_strict(&ek, m, false).map_err(|e| e.to_string())?;
+            Ok(c.to_string())
+        }
+        ["decrypt", dk_file, c] => {
+            let dk: DecryptionKey = read_json(dk_file)?;
+            let c = parse_ciphertext(&dk.encryption_key(), c)?;
+            let m: RawPlaintext = Paillier::decrypt(&dk, c);
+            Ok(m.to_string())
+        }
+        ["add", ek_file, c1, c2] => {
+            let ek: EncryptionKey = read_json(ek_file)?;
+            let c1 = parse_ciphertext(&ek, c1)?;
+            let c2 = parse_ciphertext(&ek, c2)?;
+            let c: RawCiphertext = Paillier::add(&ek, c1, c2);
+            Ok(c.to_string())
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn parse_ciphertext(ek: &EncryptionKey, c: &str) -> Result<RawCiphertext<'static>, String> {
+    let c: RawCiphertext = c.parse().map_err(|e: Error| e.to_string())?;
+    c.validate(ek).map_err(|e| e.to_string())?;
+    Ok(c)
+}
+
+fn write_json<T: serde::Serialize>(path: &str, value: &T) -> Result<(), String> {
+    let json = serde_json::to_string(value).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| format!("{}: {}", path, e))
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &str) -> Result<T, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    serde_json::from_str(&json).map_err(|e| format!("{}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(args: &[&str]) -> Result<String, String> {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        super::run(&args)
+    }
+
+    #[test]
+    fn test_end_to_end() {
+        let dir = std::env::temp_dir().join(format!("paillier-cli-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let ek_file = dir.join("ek.json");
+        let dk_file = dir.join("dk.json");
+        let ek_file = ek_file.to_str().unwrap();
+        let dk_file = dk_file.to_str().unwrap();
+
+        run(&["keygen", "512", ek_file, dk_file]).unwrap();
+        let c1 = run(&["encrypt", ek_file, "20"]).unwrap();
+        let c2 = run(&["encrypt", ek_file, "30"]).unwrap();
+        let c = run(&["add", ek_file, &c1, &c2]).unwrap();
+        assert_eq!(run(&["decrypt", dk_file, &c]).unwrap(), "50");
+
+        assert!(run(&["decrypt", dk_file, "-1"]).is_err());
+        assert!(run(&["bogus"]).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
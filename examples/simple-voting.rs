@@ -81,7 +81,7 @@ struct Voter {
 impl Voter {
     fn new(ek: &str) -> Voter {
         // deserialize encryption key
-        let ek: EncryptionKey = serde_json::from_str(&ek).unwrap();
+        let ek: EncryptionKey = serde_json::from_str(ek).unwrap();
         // generate random vote
         use rand::Rng;
         let mut rng = rand::thread_rng();
@@ -0,0 +1,11 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use kzen_paillier::*;
+
+fuzz_target!(|data: &[u8]| {
+    // must never panic, only return an error
+    let _ = serde_json::from_slice::<DecryptionKey>(data);
+    let _ = serde_json::from_slice::<MinimalDecryptionKey>(data);
+    let _ = serde_json::from_slice::<Keypair>(data);
+});
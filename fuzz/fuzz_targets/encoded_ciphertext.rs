@@ -0,0 +1,16 @@
+#![no_main]
+use std::convert::TryFrom;
+
+use libfuzzer_sys::fuzz_target;
+
+use kzen_paillier::*;
+
+fuzz_target!(|data: &[u8]| {
+    // must never panic, only return an error
+    let _ = serde_json::from_slice::<EncodedCiphertext<u64>>(data);
+    let _ = serde_json::from_slice::<EncodedCiphertext<Vec<u64>>>(data);
+    if let Ok(s) = std::str::from_utf8(data) {
+        let _ = RawCiphertext::try_from(s);
+        let _ = EncodedCiphertext::<u64>::try_from(s);
+    }
+});
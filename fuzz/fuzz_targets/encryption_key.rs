@@ -0,0 +1,10 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use kzen_paillier::*;
+
+fuzz_target!(|data: &[u8]| {
+    // must never panic, only return an error
+    let _ = serde_json::from_slice::<EncryptionKey>(data);
+    let _ = serde_json::from_slice::<MinimalEncryptionKey>(data);
+});
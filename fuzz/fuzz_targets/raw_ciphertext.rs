@@ -0,0 +1,30 @@
+#![no_main]
+use std::convert::TryFrom;
+
+use libfuzzer_sys::fuzz_target;
+
+use kzen_paillier::*;
+
+fuzz_target!(|data: &[u8]| {
+    // must never panic, only return an error
+    let ek = EncryptionKey::from(&BigInt::from(1_000_003u64 * 1_000_033));
+    let check = |c: RawCiphertext| {
+        let _ = c.validate(&ek);
+        let _ = c.validate_unit(&ek);
+    };
+
+    if let Ok(c) = RawCiphertext::from_tagged_bytes(&ek, data) {
+        check(c);
+    }
+    if let Ok(c) = serde_json::from_slice::<RawCiphertext>(data) {
+        check(c);
+    }
+    if let Ok(s) = std::str::from_utf8(data) {
+        if let Ok(c) = RawCiphertext::try_from(s) {
+            check(c);
+        }
+        if let Ok(c) = s.parse::<RawCiphertext>() {
+            check(c);
+        }
+    }
+});
@@ -0,0 +1,111 @@
+//! Running homomorphic sums that guard against plaintext overflow.
+
+use curv::arithmetic::traits::*;
+
+use crate::traits::*;
+use crate::{BigInt, EncryptionKey, Error, Paillier, RawCiphertext, RawPlaintext};
+
+/// Encrypted running sum together with an upper bound on its plaintext.
+///
+/// Each addition is declared with the largest value the added ciphertext may contain,
+/// and is rejected once the sum of these could reach the modulus `n`, at which point
+/// the plaintext would silently wrap around.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Accumulator {
+    sum: RawCiphertext<'static>,
+    count: u64,
+    bound: BigInt,
+}
+
+impl Accumulator {
+    /// Start a new sum from a fresh encryption of zero.
+    pub fn new(ek: &EncryptionKey) -> Accumulator {
+        Accumulator {
+            sum: Paillier::encrypt(ek, RawPlaintext::from(BigInt::zero())),
+            count: 0,
+            bound: BigInt::zero(),
+        }
+    }
+
+    /// Add ciphertext `c` whose plaintext is known to be at most `max_value`.
+    ///
+    /// The accumulator is left unchanged if the addition is rejected.
+    pub fn add(
+        &mut self,
+        ek: &EncryptionKey,
+        c: RawCiphertext,
+        max_value: &BigInt,
+    ) -> Result<(), Error> {
+        if max_value < &BigInt::zero() {
+            return Err(Error::PlaintextOutOfRange);
+        }
+        let bound = &self.bound + max_value;
+        if bound >= ek.n {
+            return Err(Error::BoundExceeded);
+        }
+        let sum = RawCiphertext::from(&self.sum.0 as &BigInt);
+        self.sum = Paillier::add(ek, sum, c);
+        self.bound = bound;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Current encrypted sum.
+    pub fn ciphertext(&self) -> &RawCiphertext<'static> {
+        &self.sum
+    }
+
+    /// Number of ciphertexts added so far.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Upper bound on the plaintext of the current sum.
+    pub fn bound(&self) -> &BigInt {
+        &self.bound
+    }
+
+    /// Consume the accumulator, returning the encrypted sum.
+    pub fn into_ciphertext(self) -> RawCiphertext<'static> {
+        self.sum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accumulate() {
+        let (ek, dk) = Paillier::keypair_unsafe_small(128).keys();
+
+        let mut acc = Accumulator::new(&ek);
+        for m in 1..=10 {
+            let c = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(m)));
+            acc.add(&ek, c, &BigInt::from(10)).unwrap();
+        }
+        assert_eq!(acc.count(), 10);
+        assert_eq!(acc.bound(), &BigInt::from(100));
+
+        let m = Paillier::decrypt(&dk, acc.into_ciphertext());
+        assert_eq!(m, BigInt::from(55).into());
+    }
+
+    #[test]
+    fn test_accumulate_exceeding_bound() {
+        let (ek, dk) = Paillier::keypair_unsafe_small(128).keys();
+
+        let half: BigInt = &ek.n / BigInt::from(2);
+        let mut acc = Accumulator::new(&ek);
+        let c = Paillier::encrypt(&ek, RawPlaintext::from(&half));
+        acc.add(&ek, c, &half).unwrap();
+
+        let c = Paillier::encrypt(&ek, RawPlaintext::from(&half));
+        let result = acc.add(&ek, c, &(&half + BigInt::one()));
+        assert_eq!(result, Err(Error::BoundExceeded));
+        assert_eq!(acc.count(), 1);
+
+        let m = Paillier::decrypt(&dk, acc.ciphertext());
+        assert_eq!(m, RawPlaintext::from(half));
+    }
+}
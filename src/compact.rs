@@ -0,0 +1,81 @@
+//! Compact storage of ciphertexts for memory-constrained batch jobs.
+//!
+//! A ciphertext takes up `2 * log2(n)` bits, i.e. 512 bytes for a 2048 bit modulus and
+//! 1024 bytes for a 4096 bit modulus, plus the allocation overhead of the arithmetic
+//! backend, which may reserve more than needed for results of multiplications.
+
+use curv::arithmetic::traits::*;
+
+use crate::{BigInt, EncryptionKey, RawCiphertext};
+
+/// Ciphertext stored in as few bytes as possible.
+///
+/// Trivial encryptions `1 + m * n`, e.g. from `Paillier::encrypt_trivial`, are stored as
+/// their plaintext and recomputed on `expand`; all other ciphertexts are stored as their
+/// exact big-endian bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CompactCiphertext {
+    /// Plaintext `m` of a trivial encryption, about half the size of the ciphertext.
+    Trivial(Box<[u8]>),
+    /// The same bytes as the ciphertext itself, so this only saves whatever spare
+    /// capacity the backend had reserved; real savings come from `Trivial` alone.
+    Packed(Box<[u8]>),
+}
+
+impl CompactCiphertext {
+    /// Compress canonical ciphertext `c` under `ek`.
+    pub fn compress(ek: &EncryptionKey, c: &RawCiphertext) -> CompactCiphertext {
+        let c: &BigInt = &c.0;
+        let (m, rest) = (c - BigInt::one()).div_rem(&ek.n);
+        if rest == BigInt::zero() && m >= BigInt::zero() && m < ek.n {
+            CompactCiphertext::Trivial(m.to_bytes().into_boxed_slice())
+        } else {
+            CompactCiphertext::Packed(c.to_bytes().into_boxed_slice())
+        }
+    }
+
+    /// Recover the ciphertext.
+    pub fn expand(&self, ek: &EncryptionKey) -> RawCiphertext<'static> {
+        match self {
+            CompactCiphertext::Trivial(m) => {
+                RawCiphertext::from(BigInt::from_bytes(m) * &ek.n + BigInt::one())
+            }
+            CompactCiphertext::Packed(c) => RawCiphertext::from(BigInt::from_bytes(c)),
+        }
+    }
+
+    /// Number of bytes allocated on the heap for this value.
+    pub fn heap_size(&self) -> usize {
+        match self {
+            CompactCiphertext::Trivial(bytes) | CompactCiphertext::Packed(bytes) => bytes.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::*;
+    use crate::{Paillier, RawPlaintext};
+
+    #[test]
+    fn test_compact_ciphertext() {
+        let (ek, dk) = Paillier::keypair_unsafe_small(512).keys();
+        let full_size = ek.nn.to_bytes().len();
+
+        let trivial = Paillier::encrypt_trivial(&ek, RawPlaintext::from(BigInt::from(10)));
+        let compact = CompactCiphertext::compress(&ek, &trivial);
+        assert_eq!(compact.heap_size(), 1);
+        assert_eq!(compact.expand(&ek), trivial);
+
+        let c: RawCiphertext = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(10)));
+        let compact = CompactCiphertext::compress(&ek, &c);
+        assert!(matches!(compact, CompactCiphertext::Packed(_)));
+        assert!(compact.heap_size() <= full_size);
+        assert_eq!(compact.expand(&ek), c);
+        assert_eq!(
+            Paillier::decrypt(&dk, compact.expand(&ek)),
+            RawPlaintext::from(BigInt::from(10))
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! Conversions between `BigInt` and the integer types of `num-bigint`.
+//!
+//! `BigInt` is defined by `curv`, so these are functions rather than `From` impls. Values
+//! are converted through their big-endian magnitude bytes and sign, independently of the
+//! arithmetic backend.
+
+use curv::arithmetic::traits::*;
+use num_bigint::{BigUint, Sign};
+
+use crate::BigInt;
+
+/// Convert to a signed `num_bigint::BigInt`.
+pub fn to_num_bigint(x: &BigInt) -> num_bigint::BigInt {
+    let sign = if x < &BigInt::zero() {
+        Sign::Minus
+    } else {
+        Sign::Plus
+    };
+    num_bigint::BigInt::from_bytes_be(sign, &x.abs().to_bytes())
+}
+
+/// Convert from a signed `num_bigint::BigInt`.
+pub fn from_num_bigint(x: &num_bigint::BigInt) -> BigInt {
+    let (sign, bytes) = x.to_bytes_be();
+    let magnitude = BigInt::from_bytes(&bytes);
+    match sign {
+        Sign::Minus => -magnitude,
+        _ => magnitude,
+    }
+}
+
+/// Convert to a `BigUint`, or `None` if `x` is negative.
+pub fn to_biguint(x: &BigInt) -> Option<BigUint> {
+    if x < &BigInt::zero() {
+        return None;
+    }
+    Some(BigUint::from_bytes_be(&x.to_bytes()))
+}
+
+/// Convert from a `BigUint`.
+pub fn from_biguint(x: &BigUint) -> BigInt {
+    BigInt::from_bytes(&x.to_bytes_be())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let values = [
+            BigInt::zero(),
+            BigInt::one(),
+            BigInt::from(-12345),
+            BigInt::from_str_radix("123456789012345678901234567890123456789", 10).unwrap(),
+            BigInt::from_str_radix("-98765432109876543210987654321", 10).unwrap(),
+        ];
+        for x in values.iter() {
+            let converted = to_num_bigint(x);
+            assert_eq!(converted.to_string(), x.to_str_radix(10));
+            assert_eq!(&from_num_bigint(&converted), x);
+        }
+
+        let x = BigInt::from_str_radix("123456789012345678901234567890", 10).unwrap();
+        let converted = to_biguint(&x).unwrap();
+        assert_eq!(converted.to_string(), "123456789012345678901234567890");
+        assert_eq!(from_biguint(&converted), x);
+        assert_eq!(to_biguint(&BigInt::from(-1)), None);
+    }
+}
@@ -1,14 +1,23 @@
 //! Core Paillier encryption scheme supporting ciphertext addition and plaintext multiplication.
 
 use std::borrow::{Borrow, Cow};
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops;
+use std::str::FromStr;
 
+use rand::{CryptoRng, RngCore};
 use rayon::join;
+use rayon::prelude::*;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
 
+use crate::keygen::sample_below_with_rng;
 use crate::traits::*;
 use crate::{
-    BigInt, DecryptionKey, EncryptionKey, Keypair, MinimalDecryptionKey, MinimalEncryptionKey,
-    Paillier, RawCiphertext, RawPlaintext,
+    BigInt, DecryptionKey, EncryptionKey, Error, Keypair, MinimalDecryptionKey,
+    MinimalEncryptionKey, Paillier, RawCiphertext, RawPlaintext, WithKey,
 };
 use curv::arithmetic::traits::*;
 
@@ -17,6 +26,28 @@ impl Keypair {
     pub fn keys(&self) -> (EncryptionKey, DecryptionKey) {
         (EncryptionKey::from(self), DecryptionKey::from(self))
     }
+
+    /// Public encryption key of this keypair, safe to share.
+    ///
+    /// ```
+    /// use kzen_paillier::*;
+    ///
+    /// let keypair = Paillier::keypair_with_modulus_size(512);
+    /// let ek = keypair.public_key();
+    /// let dk = keypair.secret_key();
+    ///
+    /// let c = Paillier::encrypt(&ek, 10);
+    /// let m: u64 = Paillier::decrypt(&dk, &c);
+    /// assert_eq!(m, 10);
+    /// ```
+    pub fn public_key(&self) -> EncryptionKey {
+        EncryptionKey::from(self)
+    }
+
+    /// Private decryption key of this keypair, to be kept secret.
+    pub fn secret_key(&self) -> DecryptionKey {
+        DecryptionKey::from(self)
+    }
 }
 
 impl<'p, 'q> From<(&'p BigInt, &'q BigInt)> for Keypair {
@@ -42,11 +73,12 @@ impl<'e> From<&'e EncryptionKey> for MinimalEncryptionKey {
     }
 }
 
-impl<'e> From<MinimalEncryptionKey> for EncryptionKey {
+impl From<MinimalEncryptionKey> for EncryptionKey {
     fn from(ek: MinimalEncryptionKey) -> Self {
         let nn = &ek.n * &ek.n;
+        let fingerprint = fingerprint(&ek.n);
         let n = ek.n;
-        EncryptionKey { n, nn }
+        EncryptionKey { n, nn, fingerprint }
     }
 }
 
@@ -79,6 +111,135 @@ impl<'de> Deserialize<'de> for EncryptionKey {
     }
 }
 
+impl Hash for EncryptionKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.n.to_bytes().hash(state);
+    }
+}
+
+impl Hash for MinimalEncryptionKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.n.to_bytes().hash(state);
+    }
+}
+
+impl DecryptionKey {
+    /// Carmichael function `lcm(p - 1, q - 1)` of the modulus.
+    ///
+    /// This is as secret as the primes themselves, since knowing it allows factoring `n`.
+    pub fn lambda(&self) -> &BigInt {
+        &self.lambda
+    }
+
+    /// Inverse `lambda^-1 mod n` used by direct decryption, equally secret.
+    pub fn mu(&self) -> &BigInt {
+        &self.mu
+    }
+
+    /// Encryption key matching this decryption key.
+    pub fn encryption_key(&self) -> EncryptionKey {
+        EncryptionKey {
+            n: self.n.clone(),
+            nn: self.nn.clone(),
+            fingerprint: fingerprint(&self.n),
+        }
+    }
+
+    /// Discard the secret part of the key, keeping only the encryption key.
+    ///
+    /// With the `zeroize` feature the secret values are overwritten before being dropped,
+    /// with the same best-effort caveats as for decryption intermediates.
+    pub fn into_public(mut self) -> EncryptionKey {
+        self.scrub();
+        EncryptionKey {
+            fingerprint: fingerprint(&self.n),
+            n: self.n,
+            nn: self.nn,
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    fn scrub(&mut self) {
+        use zeroize::Zeroize;
+        for x in [
+            &mut self.p,
+            &mut self.q,
+            &mut self.pp,
+            &mut self.qq,
+            &mut self.pinv,
+            &mut self.ppinv,
+            &mut self.pminusone,
+            &mut self.qminusone,
+            &mut self.hp,
+            &mut self.hq,
+            &mut self.lambda,
+            &mut self.mu,
+            &mut self.dp,
+            &mut self.dq,
+        ] {
+            x.zeroize();
+        }
+    }
+
+    #[cfg(not(feature = "zeroize"))]
+    fn scrub(&mut self) {}
+
+    /// Check that `other` decrypts exactly like this key, e.g. after importing it.
+    ///
+    /// Beyond comparing the moduli, this decrypts a fixed test ciphertext under both keys,
+    /// which catches keys whose precomputed values were corrupted or derived incorrectly.
+    pub fn functionally_equal(&self, other: &DecryptionKey) -> bool {
+        let ek = self.encryption_key();
+        if !ek.interoperable_with(&other.encryption_key()) {
+            return false;
+        }
+        let m = &self.n - BigInt::from(2);
+        let r = Randomness::from(&self.n - BigInt::one());
+        let c: RawCiphertext =
+            Paillier::encrypt_with_chosen_randomness(&ek, RawPlaintext::from(&m), &r);
+        let decrypt = |dk: &DecryptionKey| -> BigInt { Paillier::decrypt(dk, &c).into() };
+        decrypt(self) == m && decrypt(other) == m
+    }
+
+    /// Check whether `c` decrypts to zero, revealing nothing else about the plaintext.
+    ///
+    /// Meant for extracting the result of comparison protocols. The comparison with zero
+    /// is constant-time; the decryption itself is only as constant-time as the backend.
+    pub fn decrypt_is_zero(&self, c: &RawCiphertext) -> bool {
+        let m: BigInt = Paillier::decrypt(self, c).into();
+        let width = self.n.to_bytes().len();
+        let zero = vec![0u8; width];
+        to_padded_bytes(&m, width).ct_eq(&zero).into()
+    }
+
+    /// Rerandomize `c` if `keep` is set, and otherwise replace it by a fresh encryption of zero.
+    ///
+    /// Both cases perform the same operations, with the choice between `c` and the trivial
+    /// encryption `1` of zero made by a constant-time selection, so neither the output nor
+    /// the timing reveals `keep`. The fresh randomness is encrypted using CRT, and `ek`
+    /// must be the encryption key matching this key.
+    pub fn conditional_rerandomize_zero(
+        &self,
+        ek: &EncryptionKey,
+        c: &RawCiphertext,
+        keep: bool,
+    ) -> RawCiphertext<'static> {
+        let width = ek.nn.to_bytes().len();
+        let kept = to_padded_bytes(&c.0.modulus(&ek.nn), width);
+        let zero = to_padded_bytes(&BigInt::one(), width);
+        let keep = Choice::from(keep as u8);
+        let selected: Vec<u8> = zero
+            .iter()
+            .zip(&kept)
+            .map(|(z, k)| u8::conditional_select(z, k, keep))
+            .collect();
+
+        let rn: RawCiphertext = Paillier::encrypt(self, RawPlaintext::from(BigInt::zero()));
+        let d = BigInt::mod_mul(&BigInt::from_bytes(&selected), &rn.0, &ek.nn);
+        RawCiphertext(Cow::Owned(d))
+    }
+}
+
 impl<'kp> From<&'kp Keypair> for MinimalDecryptionKey {
     fn from(keypair: &'kp Keypair) -> Self {
         MinimalDecryptionKey {
@@ -97,19 +258,61 @@ impl<'e> From<&'e DecryptionKey> for MinimalDecryptionKey {
     }
 }
 
-impl<'e> From<MinimalDecryptionKey> for DecryptionKey {
-    fn from(dk: MinimalDecryptionKey) -> Self {
+/// Fails with `Error::InvalidKey` unless `p` and `q` are distinct, greater than one,
+/// and admit all inverses needed for decryption.
+impl TryFrom<MinimalDecryptionKey> for DecryptionKey {
+    type Error = Error;
+
+    fn try_from(dk: MinimalDecryptionKey) -> Result<Self, Error> {
         let p = dk.p;
         let q = dk.q;
-
-        DecryptionKey { p, q }
+        if p <= BigInt::one() || q <= BigInt::one() || p == q {
+            return Err(Error::InvalidKey);
+        }
+        let pp = &p * &p;
+        let qq = &q * &q;
+        let n = &p * &q;
+        let nn = &n * &n;
+        let pinv = BigInt::mod_inv(&p, &q).ok_or(Error::InvalidKey)?;
+        let ppinv = BigInt::mod_inv(&pp, &qq).ok_or(Error::InvalidKey)?;
+        let pminusone = &p - BigInt::one();
+        let qminusone = &q - BigInt::one();
+        let hp = h(&p, &pp, &n).ok_or(Error::InvalidKey)?;
+        let hq = h(&q, &qq, &n).ok_or(Error::InvalidKey)?;
+        let lambda = math::carmichael(&p, &q);
+        let mu = BigInt::mod_inv(&lambda, &n).ok_or(Error::InvalidKey)?;
+        let phi = &pminusone * &qminusone;
+        let dn = BigInt::mod_inv(&n, &phi).ok_or(Error::InvalidKey)?;
+        let (dp, dq) = crt_decompose(dn, &pminusone, &qminusone);
+
+        Ok(DecryptionKey {
+            p,
+            q,
+            pp,
+            qq,
+            n,
+            nn,
+            pinv,
+            ppinv,
+            pminusone,
+            qminusone,
+            hp,
+            hq,
+            lambda,
+            mu,
+            dp,
+            dq,
+        })
     }
 }
 
+/// Panics if the keypair does not hold two distinct primes, which cannot happen for
+/// keypairs produced by key generation; use `TryFrom<MinimalDecryptionKey>` for
+/// untrusted input.
 impl<'kp> From<&'kp Keypair> for DecryptionKey {
     fn from(keypair: &'kp Keypair) -> DecryptionKey {
         let minimal = MinimalDecryptionKey::from(keypair);
-        DecryptionKey::from(minimal)
+        DecryptionKey::try_from(minimal).expect("keypair primes are invalid")
     }
 }
 
@@ -123,7 +326,7 @@ impl Serialize for DecryptionKey {
 impl<'de> Deserialize<'de> for DecryptionKey {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         let minimal = MinimalDecryptionKey::deserialize(deserializer)?;
-        Ok(DecryptionKey::from(minimal))
+        DecryptionKey::try_from(minimal).map_err(serde::de::Error::custom)
     }
 }
 
@@ -133,9 +336,241 @@ pub struct Randomness(pub BigInt);
 #[derive(Debug, PartialEq)]
 pub struct PrecomputedRandomness(BigInt);
 
+/// Encryption randomness that may be revealed, e.g. when opening a ciphertext.
+#[derive(Debug, PartialEq)]
+pub struct PublicRandomness(pub BigInt);
+
+/// Encryption randomness that must never be revealed.
+///
+/// The value is not exposed and only turns into `PublicRandomness` through an explicit
+/// `reveal`, so it cannot be handed out where public randomness is expected by accident:
+///
+/// ```compile_fail
+/// use kzen_paillier::*;
+///
+/// fn publish(_: &PublicRandomness) {}
+///
+/// let ek = EncryptionKey::from(&BigInt::from(143));
+/// publish(&SecretRandomness::sample(&ek));
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct SecretRandomness(BigInt);
+
+impl SecretRandomness {
+    /// Sample uniformly from `Z_n^*`, see `Randomness::sample`.
+    pub fn sample(ek: &EncryptionKey) -> SecretRandomness {
+        SecretRandomness(Randomness::sample(ek).0)
+    }
+
+    /// Explicitly declassify the randomness.
+    pub fn reveal(self) -> PublicRandomness {
+        PublicRandomness(self.0)
+    }
+}
+
+impl From<BigInt> for SecretRandomness {
+    fn from(x: BigInt) -> SecretRandomness {
+        SecretRandomness(x)
+    }
+}
+
+impl Paillier {
+    /// Encrypt `m` with randomness that may later be revealed to open the ciphertext.
+    pub fn encrypt_with_public_randomness(
+        ek: &EncryptionKey,
+        m: RawPlaintext,
+        r: &PublicRandomness,
+    ) -> RawCiphertext<'static> {
+        let rn = Self::precompute(ek, &r.0);
+        Self::encrypt_with_chosen_randomness(ek, m, &rn)
+    }
+
+    /// Encrypt `m` with randomness that is kept secret.
+    pub fn encrypt_with_secret_randomness(
+        ek: &EncryptionKey,
+        m: RawPlaintext,
+        r: &SecretRandomness,
+    ) -> RawCiphertext<'static> {
+        let rn = Self::precompute(ek, &r.0);
+        Self::encrypt_with_chosen_randomness(ek, m, &rn)
+    }
+
+    /// Check the opening `(m, r)` of `c`, i.e. that `c` is the encryption of `m` under `r`.
+    ///
+    /// Lets the opener convince anyone holding the encryption key that `c` decrypts to `m`.
+    /// Also rejects plaintexts outside `[0, n)` and randomness outside `Z_n^*`, which could
+    /// otherwise give a valid-looking opening to a different plaintext.
+    pub fn verify_opening(
+        ek: &EncryptionKey,
+        c: &RawCiphertext,
+        m: &RawPlaintext,
+        r: &Randomness,
+    ) -> bool {
+        let value: &BigInt = m.0.borrow();
+        if value < &BigInt::zero() || value >= &ek.n || !ek.is_valid_randomness(&r.0) {
+            return false;
+        }
+        ek.verify_ciphertext_ct(c, m, r).into()
+    }
+
+    /// Recover the plaintext and randomness of `c`.
+    ///
+    /// Unlike `open`, which panics, fails with `Error::NotCoprime` if `c` shares a factor
+    /// with `n` and so is not the encryption of anything.
+    pub fn try_open(
+        dk: &DecryptionKey,
+        c: &RawCiphertext,
+    ) -> Result<(RawPlaintext<'static>, Randomness), Error> {
+        let m = Self::decrypt(dk, c);
+        let gminv = (BigInt::one() - (m.0.borrow() as &BigInt) * &dk.n) % &dk.nn;
+        let rn = (c.0.borrow() as &BigInt * gminv) % &dk.nn;
+        let r = extract_nroot(dk, &rn)?;
+        Ok((m, Randomness(r)))
+    }
+
+    /// Open `c` as `try_open` does, marking the recovered randomness as public.
+    pub fn open_public(
+        dk: &DecryptionKey,
+        c: &RawCiphertext,
+    ) -> Result<(RawPlaintext<'static>, PublicRandomness), Error> {
+        let (m, r) = Self::try_open(dk, c)?;
+        Ok((m, PublicRandomness(r.0)))
+    }
+}
+
+impl EncryptionKey {
+    /// Largest plaintext that can be encrypted without wrapping around, i.e. `n - 1`.
+    ///
+    /// There is no signed encoding in this crate; callers encoding negative values as
+    /// residues modulo `n` should restrict magnitudes to `(n - 1) / 2`.
+    pub fn max_plaintext(&self) -> BigInt {
+        &self.n - BigInt::one()
+    }
+
+    /// Base two logarithm of the number of values below `2^max_value_bits` that can be
+    /// added without the plaintext wrapping around modulo `n`.
+    ///
+    /// That is, the largest `d` with `2^d * 2^max_value_bits <= n`, so that the magnitude of
+    /// the values may be doubled `d` times before reaching `n`. Saturates at zero if values
+    /// of `max_value_bits` bits do not even fit on their own. See `Accumulator` for tracking
+    /// an exact bound at runtime.
+    pub fn max_additions(&self, max_value_bits: usize) -> u64 {
+        let modulus_bits = self.n.bit_length().saturating_sub(1);
+        modulus_bits.saturating_sub(max_value_bits) as u64
+    }
+
+    /// Cheap, non-cryptographic tag of the modulus for telling keys apart.
+    ///
+    /// Computed as the 64-bit FNV-1a hash of the big-endian bytes of `n`, and suitable
+    /// for storing alongside ciphertexts to detect when they are mixed up. The hash is
+    /// computed once when the key is constructed, so changing `n` afterwards leaves it stale.
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    /// Stable identifier of the key for logs and key management.
+    ///
+    /// The first 16 bytes of the SHA-256 hash of `n`. Unlike `fingerprint` it is
+    /// collision resistant, but like it, it is not secret and may be stored anywhere.
+    pub fn key_id(&self) -> [u8; 16] {
+        use curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
+        use curv::cryptographic_primitives::hashing::traits::Hash;
+
+        let digest = to_padded_bytes(&HSha256::create_hash(&[&self.n]), 32);
+        let mut id = [0u8; 16];
+        id.copy_from_slice(&digest[..16]);
+        id
+    }
+
+    /// Check whether `c` is the encryption of `m` under randomness `r`.
+    ///
+    /// The final comparison is constant-time, so the result does not leak through timing
+    /// which part of `c` differs; the re-encryption itself is only as constant-time as the
+    /// arithmetic backend.
+    pub fn verify_ciphertext_ct(
+        &self,
+        c: &RawCiphertext,
+        m: &RawPlaintext,
+        r: &Randomness,
+    ) -> Choice {
+        let expected: RawCiphertext = Paillier::encrypt_with_chosen_randomness(
+            self,
+            RawPlaintext::from(m.0.borrow() as &BigInt),
+            r,
+        );
+        let width = self.nn.to_bytes().len();
+        to_padded_bytes(&expected.0, width).ct_eq(&to_padded_bytes(&c.0, width))
+    }
+
+    /// Approximate symmetric-equivalent security level of the modulus in bits.
+    ///
+    /// Follows the table of NIST SP 800-57 Part 1 for factoring-based keys, with
+    /// 1024, 2048, 3072, 7680 and 15360 bit moduli giving 80, 112, 128, 192 and 256 bits,
+    /// interpolating linearly in between and capping at 256. Smaller moduli are scaled
+    /// down linearly. The modulus size is rounded up to whole bytes first, so that a
+    /// 2047 bit product of two 1024 bit primes counts as a 2048 bit modulus.
+    pub fn security_bits(&self) -> u32 {
+        const TABLE: [(u64, u64); 6] = [
+            (0, 0),
+            (1024, 80),
+            (2048, 112),
+            (3072, 128),
+            (7680, 192),
+            (15360, 256),
+        ];
+        let bits = self.n.bit_length().div_ceil(8) as u64 * 8;
+        let upper = TABLE.iter().position(|(size, _)| *size >= bits);
+        let security = match upper {
+            None => 256,
+            Some(0) => 0,
+            Some(i) => {
+                let (size0, security0) = TABLE[i - 1];
+                let (size1, security1) = TABLE[i];
+                security0 + (bits - size0) * (security1 - security0) / (size1 - size0)
+            }
+        };
+        security as u32
+    }
+
+    /// Check whether ciphertexts under `other` can be used with this key and vice versa.
+    ///
+    /// The generator is always `n + 1` in this crate, so this amounts to comparing the
+    /// moduli, including the precomputed square.
+    pub fn interoperable_with(&self, other: &EncryptionKey) -> bool {
+        self.n == other.n && self.nn == other.nn
+    }
+
+    /// Check that `r` is usable as encryption randomness, i.e. that it lies in `Z_n^*`.
+    pub fn is_valid_randomness(&self, r: &BigInt) -> bool {
+        r > &BigInt::zero() && r < &self.n && BigInt::gcd(r, &self.n) == BigInt::one()
+    }
+}
+
 impl Randomness {
+    /// Sample uniformly from `Z_n^*`.
+    ///
+    /// Candidates are drawn by rejection sampling below `n` and re-drawn if not coprime to
+    /// `n`, never by reducing a larger random value modulo `n`, which would be biased.
     pub fn sample(ek: &EncryptionKey) -> Randomness {
-        Randomness(BigInt::sample_below(&ek.n))
+        loop {
+            let r = BigInt::sample_below(&ek.n);
+            if ek.is_valid_randomness(&r) {
+                return Randomness(r);
+            }
+        }
+    }
+
+    /// Sample as `sample` but drawing from `rng`.
+    pub(crate) fn sample_with_rng<R: RngCore + ?Sized>(
+        ek: &EncryptionKey,
+        rng: &mut R,
+    ) -> Randomness {
+        loop {
+            let r = sample_below_with_rng(&ek.n, rng);
+            if ek.is_valid_randomness(&r) {
+                return Randomness(r);
+            }
+        }
     }
 }
 
@@ -187,9 +622,178 @@ impl<'b> From<RawCiphertext<'b>> for BigInt {
     }
 }
 
+impl<'b> RawCiphertext<'b> {
+    /// Reduce ciphertext into its canonical form in `[0, n^2)`.
+    ///
+    /// Integers that are congruent modulo `n^2` decrypt to the same plaintext, so comparing
+    /// ciphertexts for equality is only meaningful on canonical forms. Encryption and the
+    /// homomorphic operations always return canonical ciphertexts.
+    pub fn canonicalize(&self, ek: &EncryptionKey) -> RawCiphertext<'static> {
+        RawCiphertext(Cow::Owned(self.0.modulus(&ek.nn)))
+    }
+
+    /// Best-effort attempt to release spare capacity of an owned ciphertext.
+    ///
+    /// Results of homomorphic operations may hold on to the larger buffers used for the
+    /// intermediate products. This replaces the value with a clone, which the backends
+    /// currently allocate to fit, but the crate does not measure or guarantee that any
+    /// memory is freed. Borrowed ciphertexts are left as they are. See `compact` for
+    /// storing many ciphertexts with a known size.
+    pub fn shrink_to_fit(&mut self) {
+        if let Cow::Owned(c) = &mut self.0 {
+            *c = c.clone();
+        }
+    }
+
+    /// Check that the ciphertext lies in `[0, n^2)`.
+    pub fn validate(&self, ek: &EncryptionKey) -> Result<(), Error> {
+        let c: &BigInt = self.0.borrow();
+        if c < &BigInt::zero() || c >= &ek.nn {
+            return Err(Error::CiphertextOutOfRange);
+        }
+        Ok(())
+    }
+
+    /// Check that the ciphertext lies in `Z_{n^2}^*`, i.e. is in range and coprime to `n`.
+    ///
+    /// Honestly generated ciphertexts always are; one sharing a factor with `n` can only
+    /// come from a buggy or malicious source, and reveals the factorization of `n`.
+    pub fn validate_unit(&self, ek: &EncryptionKey) -> Result<(), Error> {
+        self.validate(ek)?;
+        if BigInt::gcd(self.0.borrow(), &ek.n) != BigInt::one() {
+            return Err(Error::NotCoprime);
+        }
+        Ok(())
+    }
+
+    /// Serialize ciphertext prefixed with the 8-byte fingerprint of `ek`.
+    ///
+    /// The fingerprint is stored big-endian and followed by the big-endian bytes of the
+    /// ciphertext, see `from_tagged_bytes`.
+    pub fn to_tagged_bytes(&self, ek: &EncryptionKey) -> Vec<u8> {
+        let mut bytes = ek.fingerprint().to_be_bytes().to_vec();
+        bytes.extend((self.0.borrow() as &BigInt).to_bytes());
+        bytes
+    }
+
+    /// Deserialize ciphertext produced by `to_tagged_bytes`, checking it belongs to `ek`.
+    pub fn from_tagged_bytes(
+        ek: &EncryptionKey,
+        bytes: &[u8],
+    ) -> Result<RawCiphertext<'static>, Error> {
+        if bytes.len() < 8 {
+            return Err(Error::InvalidEncoding);
+        }
+        let (tag, c) = bytes.split_at(8);
+        if tag != ek.fingerprint().to_be_bytes() {
+            return Err(Error::ModulusMismatch);
+        }
+        let c = RawCiphertext(Cow::Owned(BigInt::from_bytes(c)));
+        c.validate(ek)?;
+        Ok(c)
+    }
+}
+
+/// Parse a ciphertext from its decimal representation.
+///
+/// Only rejects strings that can never be a ciphertext; the upper bound depends on the
+/// key and must be checked separately using `validate`.
+impl<'s> TryFrom<&'s str> for RawCiphertext<'static> {
+    type Error = Error;
+
+    fn try_from(s: &'s str) -> Result<Self, Error> {
+        let c = parse_nonnegative(s)?;
+        Ok(RawCiphertext(Cow::Owned(c)))
+    }
+}
+
+/// Serialize ciphertext as a decimal string.
+///
+/// Decimal rather than hex, matching the encoding of keys and all other big integers
+/// in this crate, so serialized ciphertexts and keys stay consistent.
+impl<'b> Serialize for RawCiphertext<'b> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serialize::bigint::serialize(self.0.borrow(), serializer)
+    }
+}
+
+/// Deserialize ciphertext from a decimal string; hex is not accepted.
+///
+/// This does not guarantee a valid ciphertext, since the range depends on the key;
+/// deserialized ciphertexts from untrusted sources must be checked using `validate`.
+impl<'de> Deserialize<'de> for RawCiphertext<'static> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let c = crate::serialize::bigint::deserialize(deserializer)?;
+        Ok(RawCiphertext(Cow::Owned(c)))
+    }
+}
+
+impl FromStr for RawCiphertext<'static> {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        RawCiphertext::try_from(s)
+    }
+}
+
+impl<'b> fmt::Display for RawCiphertext<'b> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0.to_str_radix(10))
+    }
+}
+
+impl FromStr for RawPlaintext<'static> {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let m = BigInt::from_str_radix(s, 10).map_err(|_| Error::InvalidEncoding)?;
+        Ok(RawPlaintext(Cow::Owned(m)))
+    }
+}
+
+impl<'b> fmt::Display for RawPlaintext<'b> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0.to_str_radix(10))
+    }
+}
+
+/// Parse an encryption key from the decimal representation of its modulus.
+impl FromStr for EncryptionKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let n = parse_nonnegative(s)?;
+        if n <= BigInt::one() {
+            return Err(Error::InvalidEncoding);
+        }
+        Ok(EncryptionKey::from(MinimalEncryptionKey { n }))
+    }
+}
+
+impl fmt::Display for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.n.to_str_radix(10))
+    }
+}
+
+/// Displays only a placeholder, never the secret primes.
+impl fmt::Display for DecryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DecryptionKey(<redacted>)")
+    }
+}
+
+fn parse_nonnegative(s: &str) -> Result<BigInt, Error> {
+    let x = BigInt::from_str_radix(s, 10).map_err(|_| Error::InvalidEncoding)?;
+    if x < BigInt::zero() {
+        return Err(Error::InvalidEncoding);
+    }
+    Ok(x)
+}
+
 impl<'m, 'd> Encrypt<EncryptionKey, RawPlaintext<'m>, RawCiphertext<'d>> for Paillier {
     fn encrypt(ek: &EncryptionKey, m: RawPlaintext<'m>) -> RawCiphertext<'d> {
-        let r = Randomness::sample(&ek);
+        let r = Randomness::sample(ek);
         let rn = BigInt::mod_pow(&r.0, &ek.n, &ek.nn);
         let gm: BigInt = (m.0.borrow() as &BigInt * &ek.n + 1) % &ek.nn;
         let c = (gm * rn) % &ek.nn;
@@ -197,6 +801,350 @@ impl<'m, 'd> Encrypt<EncryptionKey, RawPlaintext<'m>, RawCiphertext<'d>> for Pai
     }
 }
 
+impl Paillier {
+    /// Encrypt `m` with randomness `r = 1`, i.e. compute `g^m = 1 + m * n mod n^2`.
+    ///
+    /// **This is not semantically secure**: anyone can recompute the ciphertext, and hence
+    /// test it against guesses of `m`. It is only meant for public constants that all
+    /// parties must agree on, e.g. as operands of homomorphic operations. Negative `m` is
+    /// taken modulo `n`.
+    pub fn encrypt_trivial(ek: &EncryptionKey, m: RawPlaintext) -> RawCiphertext<'static> {
+        let m = (m.0.borrow() as &BigInt).modulus(&ek.n);
+        let c = (m * &ek.n + 1) % &ek.nn;
+        RawCiphertext(Cow::Owned(c))
+    }
+
+    /// Encrypt plaintext `m` after checking that it lies in `[0, n)`.
+    ///
+    /// Encrypting `n` is equivalent to encrypting zero and almost always a bug in the
+    /// caller, so it is rejected explicitly; with `reject_zero` set, so is zero itself.
+    pub fn encrypt_strict<'m, 'd>(
+        ek: &EncryptionKey,
+        m: RawPlaintext<'m>,
+        reject_zero: bool,
+    ) -> Result<RawCiphertext<'d>, Error> {
+        let value: &BigInt = m.0.borrow();
+        if value == &ek.n {
+            return Err(Error::PlaintextEqualsModulus);
+        }
+        if value < &BigInt::zero() || value > &ek.max_plaintext() {
+            return Err(Error::PlaintextOutOfRange);
+        }
+        if reject_zero && value == &BigInt::zero() {
+            return Err(Error::ZeroPlaintext);
+        }
+        Ok(Self::encrypt(ek, m))
+    }
+}
+
+impl Paillier {
+    /// Multiply the other party's ciphertext `c_a` of `a` by own secret `b`, splitting the
+    /// product into additive shares.
+    ///
+    /// Returns a ciphertext of `a * b - beta` to send back to the key holder, who decrypts
+    /// it into their share `alpha`, together with the own share `beta`, such that
+    /// `alpha + beta = a * b mod n`. The mask `beta` is uniform in `Z_n`, hiding `a * b`
+    /// from the key holder, and the ciphertext is freshly randomised, hiding `b`.
+    pub fn mul_and_share<R: RngCore + CryptoRng>(
+        ek: &EncryptionKey,
+        c_a: &RawCiphertext,
+        b: &BigInt,
+        rng: &mut R,
+    ) -> (RawCiphertext<'static>, BigInt) {
+        let beta = sample_below_with_rng(&ek.n, rng);
+        let r = Randomness::sample_with_rng(ek, rng);
+        let mask: RawCiphertext = Paillier::encrypt_with_chosen_randomness(
+            ek,
+            RawPlaintext::from(BigInt::mod_sub(&BigInt::zero(), &beta, &ek.n)),
+            &r,
+        );
+        let product: RawCiphertext = Paillier::mul(
+            ek,
+            RawCiphertext::from(c_a.0.borrow() as &BigInt),
+            RawPlaintext::from(b.modulus(&ek.n)),
+        );
+        (Paillier::add(ek, product, mask), beta)
+    }
+
+    /// Encrypt a fresh plaintext drawn uniformly from `[0, 2^bits)` using `rng`.
+    ///
+    /// Returns the ciphertext together with the plaintext, e.g. for committing to a random
+    /// value and opening it later. Fails with `BoundExceeded` unless `2^bits < n`.
+    pub fn encrypt_random<R: RngCore + CryptoRng>(
+        ek: &EncryptionKey,
+        bits: usize,
+        rng: &mut R,
+    ) -> Result<(RawCiphertext<'static>, BigInt), Error> {
+        let bound = BigInt::one() << bits;
+        if bound >= ek.n {
+            return Err(Error::BoundExceeded);
+        }
+        let m = sample_below_with_rng(&bound, rng);
+        let r = Randomness::sample_with_rng(ek, rng);
+        let c = Paillier::encrypt_with_chosen_randomness(ek, RawPlaintext::from(&m), &r);
+        Ok((c, m))
+    }
+}
+
+/// Homomorphic operations validating their ciphertext operands with `validate_unit`.
+///
+/// The validation costs a GCD per operand, which may be skipped using `add` and `mul` for
+/// ciphertexts from trusted sources.
+impl Paillier {
+    pub fn checked_add(
+        ek: &EncryptionKey,
+        c1: &RawCiphertext,
+        c2: &RawCiphertext,
+    ) -> Result<RawCiphertext<'static>, Error> {
+        c1.validate_unit(ek)?;
+        c2.validate_unit(ek)?;
+        Ok(Self::add(
+            ek,
+            RawCiphertext::from(c1.0.borrow() as &BigInt),
+            RawCiphertext::from(c2.0.borrow() as &BigInt),
+        ))
+    }
+
+    pub fn checked_mul(
+        ek: &EncryptionKey,
+        c: &RawCiphertext,
+        m: &RawPlaintext,
+    ) -> Result<RawCiphertext<'static>, Error> {
+        c.validate_unit(ek)?;
+        Ok(Self::mul(
+            ek,
+            RawCiphertext::from(c.0.borrow() as &BigInt),
+            RawPlaintext::from(m.0.borrow() as &BigInt),
+        ))
+    }
+}
+
+/// Boolean operations on encryptions of 0/1-valued plaintexts.
+///
+/// Paillier only supports multiplying a ciphertext with a plaintext, not with another
+/// ciphertext, so these are limited to the case where one of the operands is a known bit:
+/// AND is computed as `a * bit` and OR as `a + bit - a * bit = a * (1 - bit) + bit`.
+/// The results are deterministic in the input ciphertext and should be rerandomized
+/// before being shared.
+impl Paillier {
+    pub fn and_with_plaintext<'c, 'd>(
+        ek: &EncryptionKey,
+        c: RawCiphertext<'c>,
+        bit: bool,
+    ) -> RawCiphertext<'d> {
+        Self::mul(ek, c, RawPlaintext::from(BigInt::from(bit as u32)))
+    }
+
+    pub fn or_with_plaintext<'c, 'd>(
+        ek: &EncryptionKey,
+        c: RawCiphertext<'c>,
+        bit: bool,
+    ) -> RawCiphertext<'d> {
+        let kept = Self::mul(ek, c, RawPlaintext::from(BigInt::from(!bit as u32)));
+        Self::add(ek, kept, RawPlaintext::from(BigInt::from(bit as u32)))
+    }
+}
+
+impl Paillier {
+    /// Encrypt each of the lowest `num_bits` bits of `m` separately, least significant first.
+    ///
+    /// Fails with `PlaintextOutOfRange` if `m` is negative or does not fit into `num_bits` bits.
+    pub fn encrypt_bits(
+        ek: &EncryptionKey,
+        m: &BigInt,
+        num_bits: usize,
+    ) -> Result<Vec<RawCiphertext<'static>>, Error> {
+        if m < &BigInt::zero() || m.bit_length() > num_bits {
+            return Err(Error::PlaintextOutOfRange);
+        }
+        let bits = (0..num_bits)
+            .map(|i| {
+                let bit = BigInt::from(m.test_bit(i) as u32);
+                Self::encrypt(ek, RawPlaintext::from(bit))
+            })
+            .collect();
+        Ok(bits)
+    }
+
+    /// Decrypt bitwise encryptions as produced by `encrypt_bits` and reassemble the value.
+    ///
+    /// Computes `sum m_i 2^i` for the plaintexts `m_i`, without checking that they are bits.
+    pub fn decrypt_bits(dk: &DecryptionKey, bits: &[RawCiphertext]) -> BigInt {
+        bits.iter().rev().fold(BigInt::zero(), |acc, c| {
+            let bit: BigInt = Self::decrypt(dk, c).into();
+            (acc << 1) + bit
+        })
+    }
+}
+
+impl Paillier {
+    /// Homomorphically compute `a * x + b` for the plaintext `x` of `c`, i.e. `c^a * g^b mod n^2`.
+    ///
+    /// Negative `a` and `b` are taken modulo `n`. The result is deterministic in the input
+    /// ciphertext and should be rerandomized before being shared.
+    pub fn affine<'c, 'd>(
+        ek: &EncryptionKey,
+        c: RawCiphertext<'c>,
+        a: i64,
+        b: i64,
+    ) -> RawCiphertext<'d> {
+        let ca = BigInt::mod_pow(c.0.borrow(), &BigInt::from(a.unsigned_abs()), &ek.nn);
+        let ca = if a < 0 {
+            Self::neg(ek, RawCiphertext::from(ca))
+        } else {
+            RawCiphertext::from(ca)
+        };
+        Self::add(
+            ek,
+            ca,
+            RawPlaintext::from(bigint_from_i64(b).modulus(&ek.n)),
+        )
+    }
+
+    /// Homomorphically evaluate the polynomial with encrypted coefficients at point `x`.
+    ///
+    /// Coefficient `i` of `coefficients` belongs to `x^i`, and the result encrypts
+    /// `sum a_i x^i mod n` computed using Horner's method; negative `x` is taken modulo `n`.
+    /// Without coefficients this is the trivial, unrandomized encryption `1` of zero.
+    pub fn eval_poly(
+        ek: &EncryptionKey,
+        coefficients: &[RawCiphertext],
+        x: i64,
+    ) -> RawCiphertext<'static> {
+        let x_abs = BigInt::from(x.unsigned_abs());
+        coefficients
+            .iter()
+            .rev()
+            .fold(RawCiphertext::from(BigInt::one()), |acc, c| {
+                let acc = RawCiphertext::from(BigInt::mod_pow(&acc.0, &x_abs, &ek.nn));
+                let acc = if x < 0 { Self::neg(ek, acc) } else { acc };
+                Self::add(ek, acc, RawCiphertext::from(c.0.borrow() as &BigInt))
+            })
+    }
+
+    /// Coefficients of the polynomial `prod (x - s_i)` whose roots are the elements of `set`.
+    ///
+    /// Coefficient `i` belongs to `x^i`, as expected by `eval_poly`. The holder of the
+    /// decryption key encrypts the coefficients, and another party evaluates them at its
+    /// own element `y`, getting an encryption of zero exactly when `y` is in the set. Before
+    /// returning the result, the evaluating party should multiply it by a random non-zero
+    /// plaintext and rerandomize it, so that nothing but membership is revealed.
+    ///
+    /// Returns `None` if a coefficient does not fit into an `i64`.
+    pub fn encode_set_polynomial(set: &[i64]) -> Option<Vec<i64>> {
+        let mut coefficients = vec![1i64];
+        for s in set {
+            // multiply by (x - s)
+            let mut next = vec![0i64; coefficients.len() + 1];
+            for (i, a) in coefficients.iter().enumerate() {
+                next[i + 1] = next[i + 1].checked_add(*a)?;
+                next[i] = next[i].checked_sub(a.checked_mul(*s)?)?;
+            }
+            coefficients = next;
+        }
+        Some(coefficients)
+    }
+
+    /// Homomorphically compute `sum w_i x_i mod n` for the plaintexts `x_i` of `cs`.
+    ///
+    /// Negative weights are taken modulo `n`. Like `affine`, the result is deterministic
+    /// in the inputs and should be rerandomized before being shared.
+    pub fn linear_combination(
+        ek: &EncryptionKey,
+        weights: &[i64],
+        cs: &[RawCiphertext],
+    ) -> Result<RawCiphertext<'static>, Error> {
+        if weights.len() != cs.len() {
+            return Err(Error::DimensionMismatch);
+        }
+        let sum = weights
+            .iter()
+            .zip(cs)
+            .fold(RawCiphertext::from(BigInt::one()), |acc, (w, c)| {
+                let term = BigInt::mod_pow(c.0.borrow(), &BigInt::from(w.unsigned_abs()), &ek.nn);
+                let term = RawCiphertext::from(term);
+                let term = if *w < 0 { Self::neg(ek, term) } else { term };
+                Self::add(ek, acc, term)
+            });
+        Ok(sum)
+    }
+
+    /// Homomorphically multiply the public matrix `w` with the encrypted vector `x`.
+    ///
+    /// Each row of `w` gives one `linear_combination` of `x`, with rows evaluated in
+    /// parallel. All rows must have the length of `x`.
+    pub fn matvec(
+        ek: &EncryptionKey,
+        w: &[Vec<i64>],
+        x: &[RawCiphertext],
+    ) -> Result<Vec<RawCiphertext<'static>>, Error> {
+        w.par_iter()
+            .map(|row| Self::linear_combination(ek, row, x))
+            .collect()
+    }
+
+    /// Homomorphically add the public vector `b` to the encrypted vector `x` element-wise.
+    ///
+    /// Elements are processed in parallel, and negative entries of `b` are taken modulo `n`.
+    pub fn add_plaintext_vec(
+        ek: &EncryptionKey,
+        x: &[RawCiphertext],
+        b: &[i64],
+    ) -> Result<Vec<RawCiphertext<'static>>, Error> {
+        if x.len() != b.len() {
+            return Err(Error::DimensionMismatch);
+        }
+        let sums = x
+            .par_iter()
+            .zip(b)
+            .map(|(c, b)| {
+                Self::add(
+                    ek,
+                    RawCiphertext::from(c.0.borrow() as &BigInt),
+                    RawPlaintext::from(bigint_from_i64(*b).modulus(&ek.n)),
+                )
+            })
+            .collect();
+        Ok(sums)
+    }
+}
+
+/// Homomorphic sum over an iterator of ciphertexts, e.g. `cs.iter().homomorphic_sum(&ek)`.
+pub trait HomomorphicSum<'a, 'c: 'a>: Iterator<Item = &'a RawCiphertext<'c>> + Sized {
+    /// Ciphertext of the sum of all plaintexts modulo `n`; the trivial `1` if empty.
+    fn homomorphic_sum(self, ek: &EncryptionKey) -> RawCiphertext<'static> {
+        self.fold(RawCiphertext::from(BigInt::one()), |acc, c| {
+            Paillier::add(ek, acc, RawCiphertext::from(c.0.borrow() as &BigInt))
+        })
+    }
+}
+
+impl<'a, 'c: 'a, I: Iterator<Item = &'a RawCiphertext<'c>>> HomomorphicSum<'a, 'c> for I {}
+
+/// Homomorphic inner product over an iterator of pairs of ciphertexts and plaintexts,
+/// e.g. `cs.iter().zip(&ms).homomorphic_product_with_scalars(&ek)`.
+pub trait HomomorphicScalarProduct<'a, 'c: 'a, 'm: 'a>:
+    Iterator<Item = (&'a RawCiphertext<'c>, &'a RawPlaintext<'m>)> + Sized
+{
+    /// Ciphertext of `sum m_i x_i mod n` for the plaintexts `x_i` of the ciphertexts and
+    /// the scalars `m_i`; the trivial `1` if empty.
+    fn homomorphic_product_with_scalars(self, ek: &EncryptionKey) -> RawCiphertext<'static> {
+        self.fold(RawCiphertext::from(BigInt::one()), |acc, (c, m)| {
+            let term: RawCiphertext = Paillier::mul(
+                ek,
+                RawCiphertext::from(c.0.borrow() as &BigInt),
+                RawPlaintext::from(m.0.borrow() as &BigInt),
+            );
+            Paillier::add(ek, acc, term)
+        })
+    }
+}
+
+impl<'a, 'c: 'a, 'm: 'a, I> HomomorphicScalarProduct<'a, 'c, 'm> for I where
+    I: Iterator<Item = (&'a RawCiphertext<'c>, &'a RawPlaintext<'m>)>
+{
+}
+
 impl<'m, 'r, 'd>
     EncryptWithChosenRandomness<EncryptionKey, RawPlaintext<'m>, &'r Randomness, RawCiphertext<'d>>
     for Paillier
@@ -213,6 +1161,45 @@ impl<'m, 'r, 'd>
     }
 }
 
+#[cfg(feature = "unsafe-deterministic-encryption")]
+impl Paillier {
+    /// Encrypt plaintext `m` using randomness derived from `m` itself.
+    ///
+    /// **Insecure**: this gives up semantic security, since equal plaintexts under the same
+    /// `ek` and `key` always give equal ciphertexts, revealing which values repeat. Only use
+    /// it for fields that must be deduplicated and for which this leak is acceptable.
+    ///
+    /// The randomness is an HMAC-SHA512 of `n` and `m` under the secret `key`, expanded to
+    /// the size of `n`; without `key` one cannot test whether a ciphertext encrypts a guess.
+    #[allow(non_snake_case)]
+    pub fn encrypt_deterministic_UNSAFE<'m, 'd>(
+        ek: &EncryptionKey,
+        key: &BigInt,
+        m: RawPlaintext<'m>,
+    ) -> RawCiphertext<'d> {
+        use curv::cryptographic_primitives::hashing::hmac_sha512::HMacSha512;
+        use curv::cryptographic_primitives::hashing::traits::KeyedHash;
+
+        // expand to 128 bits more than n to make the reduction modulo n close to uniform
+        let blocks = (ek.n.bit_length() + 128).div_ceil(512);
+        let mut counter = 0u32;
+        let r = loop {
+            let mut r = BigInt::zero();
+            for _ in 0..blocks {
+                let block =
+                    HMacSha512::create_hmac(key, &[&ek.n, m.0.borrow(), &BigInt::from(counter)]);
+                r = (r << 512) + block;
+                counter += 1;
+            }
+            let r = r.modulus(&ek.n);
+            if ek.is_valid_randomness(&r) {
+                break Randomness(r);
+            }
+        };
+        Paillier::encrypt_with_chosen_randomness(ek, m, &r)
+    }
+}
+
 impl<'m, 'r, 'd>
     EncryptWithChosenRandomness<
         EncryptionKey,
@@ -234,26 +1221,22 @@ impl<'m, 'r, 'd>
 
 impl<'m, 'd> Encrypt<DecryptionKey, RawPlaintext<'m>, RawCiphertext<'d>> for Paillier {
     fn encrypt(dk: &DecryptionKey, m: RawPlaintext<'m>) -> RawCiphertext<'d> {
-        let dk_pp = &dk.p * &dk.p;
-        let dk_qq = &dk.q * &dk.q;
-        let dk_n = &dk.q * &dk.p;
-        let dk_ppinv = BigInt::mod_inv(&dk_pp, &dk_qq).unwrap();
-        let (mp, mq) = crt_decompose(m.0.borrow(), &dk_pp, &dk_qq);
+        let (mp, mq) = crt_decompose(m.0.borrow(), &dk.pp, &dk.qq);
         let (cp, cq) = join(
             || {
                 let rp = BigInt::sample_below(&dk.p);
-                let rnp = BigInt::mod_pow(&rp, &dk_n, &dk_pp);
-                let gmp = (1 + mp * &dk_n) % &dk_pp; // TODO[Morten] maybe there's more to get here
-                (gmp * rnp) % &dk_pp
+                let rnp = BigInt::mod_pow(&rp, &dk.n, &dk.pp);
+                let gmp = (1 + mp * &dk.n) % &dk.pp; // TODO[Morten] maybe there's more to get here
+                (gmp * rnp) % &dk.pp
             },
             || {
                 let rq = BigInt::sample_below(&dk.q);
-                let rnq = BigInt::mod_pow(&rq, &dk_n, &dk_qq);
-                let gmq = (1 + mq * &dk_n) % &dk_qq; // TODO[Morten] maybe there's more to get here
-                (gmq * rnq) % &dk_qq
+                let rnq = BigInt::mod_pow(&rq, &dk.n, &dk.qq);
+                let gmq = (1 + mq * &dk.n) % &dk.qq; // TODO[Morten] maybe there's more to get here
+                (gmq * rnq) % &dk.qq
             },
         );
-        let c = crt_recombine(cp, cq, &dk_pp, &dk_qq, &dk_ppinv);
+        let c = crt_recombine(cp, cq, &dk.pp, &dk.qq, &dk.ppinv);
         RawCiphertext(Cow::Owned(c))
     }
 }
@@ -267,25 +1250,21 @@ impl<'m, 'r, 'd>
         m: RawPlaintext<'m>,
         r: &'r Randomness,
     ) -> RawCiphertext<'d> {
-        let dk_pp = &dk.p * &dk.p;
-        let dk_qq = &dk.q * &dk.q;
-        let dk_n = &dk.q * &dk.p;
-        let dk_ppinv = BigInt::mod_inv(&dk_pp, &dk_qq).unwrap();
-        let (mp, mq) = crt_decompose(m.0.borrow(), &dk_pp, &dk_qq);
-        let (rp, rq) = crt_decompose(&r.0, &dk_pp, &dk_qq);
+        let (mp, mq) = crt_decompose(m.0.borrow(), &dk.pp, &dk.qq);
+        let (rp, rq) = crt_decompose(&r.0, &dk.pp, &dk.qq);
         let (cp, cq) = join(
             || {
-                let rnp = BigInt::mod_pow(&rp, &dk_n, &dk_pp);
-                let gmp = (1 + mp * &dk_n) % &dk_pp; // TODO[Morten] maybe there's more to get here
-                (gmp * rnp) % &dk_pp
+                let rnp = BigInt::mod_pow(&rp, &dk.n, &dk.pp);
+                let gmp = (1 + mp * &dk.n) % &dk.pp; // TODO[Morten] maybe there's more to get here
+                (gmp * rnp) % &dk.pp
             },
             || {
-                let rnq = BigInt::mod_pow(&rq, &dk_n, &dk_qq);
-                let gmq = (1 + mq * &dk_n) % &dk_qq; // TODO[Morten] maybe there's more to get here
-                (gmq * rnq) % &dk_qq
+                let rnq = BigInt::mod_pow(&rq, &dk.n, &dk.qq);
+                let gmq = (1 + mq * &dk.n) % &dk.qq; // TODO[Morten] maybe there's more to get here
+                (gmq * rnq) % &dk.qq
             },
         );
-        let c = crt_recombine(cp, cq, &dk_pp, &dk_qq, &dk_ppinv);
+        let c = crt_recombine(cp, cq, &dk.pp, &dk.qq, &dk.ppinv);
         RawCiphertext(Cow::Owned(c))
     }
 }
@@ -303,10 +1282,8 @@ impl<'m, 'r, 'd>
         m: RawPlaintext<'m>,
         rn: &'r PrecomputedRandomness,
     ) -> RawCiphertext<'d> {
-        let dk_n = &dk.q * &dk.p;
-        let dk_nn = &dk_n * &dk_n;
-        let gm = (1 + m.0.borrow() as &BigInt * &dk_n) % &dk_nn;
-        let c = (gm * &rn.0) % &dk_nn;
+        let gm = (1 + m.0.borrow() as &BigInt * &dk.n) % &dk.nn;
+        let c = (gm * &rn.0) % &dk.nn;
         RawCiphertext(Cow::Owned(c))
     }
 }
@@ -322,8 +1299,8 @@ impl<'ek, 'r> PrecomputeRandomness<&'ek EncryptionKey, &'r BigInt, PrecomputedRa
 
 impl<'c, 'd> Rerandomize<EncryptionKey, RawCiphertext<'c>, RawCiphertext<'d>> for Paillier {
     fn rerandomize(ek: &EncryptionKey, c: RawCiphertext<'c>) -> RawCiphertext<'d> {
-        let r = BigInt::sample_below(&ek.n);
-        let rn = BigInt::mod_pow(&r, &ek.n, &ek.nn);
+        let r = Randomness::sample(ek);
+        let rn = BigInt::mod_pow(&r.0, &ek.n, &ek.nn);
         let d = (c.0.borrow() as &BigInt * rn) % &ek.nn;
         RawCiphertext(Cow::Owned(d))
     }
@@ -343,52 +1320,122 @@ impl<'c, 'm> Decrypt<DecryptionKey, RawCiphertext<'c>, RawPlaintext<'m>> for Pai
 /// Efficient decryption using CRT based on [Paillier99, section 7](http://citeseerx.ist.psu.edu/viewdoc/download?doi=10.1.1.112.4035&rep=rep1&type=pdf)
 impl<'c, 'm> Decrypt<DecryptionKey, &'c RawCiphertext<'c>, RawPlaintext<'m>> for Paillier {
     fn decrypt(dk: &DecryptionKey, c: &'c RawCiphertext<'c>) -> RawPlaintext<'m> {
-        let dk_qq = &dk.q * &dk.q;
-        let dk_pp = &dk.p * &dk.p;
-        let dk_n = &dk.p * &dk.q;
-        let dk_pinv = BigInt::mod_inv(&dk.p, &dk.q).unwrap();
-        let dk_qminusone = &dk.q - BigInt::one();
-        let dk_pminusone = &dk.p - BigInt::one();
-        let dk_hp = h(&dk.p, &dk_pp, &dk_n);
-        let dk_hq = h(&dk.q, &dk_qq, &dk_n);
-        let (cp, cq) = crt_decompose(c.0.borrow(), &dk_pp, &dk_qq);
-        // decrypt in parallel with respectively p and q
-        let (mp, mq) = join(
-            || {
-                // process using p
-                let dp = BigInt::mod_pow(&cp, &dk_pminusone, &dk_pp);
-                let lp = l(&dp, &dk.p);
-                (&lp * &dk_hp) % &dk.p
-            },
-            || {
-                // process using q
-                let dq = BigInt::mod_pow(&cq, &dk_qminusone, &dk_qq);
-                let lq = l(&dq, &dk.q);
-                (&lq * &dk_hq) % &dk.q
-            },
-        );
-        // perform CRT
-        let m = crt_recombine(mp, mq, &dk.p, &dk.q, &dk_pinv);
-        RawPlaintext(Cow::Owned(m))
+        Self::decrypt_with_threshold(dk, c, CRT_THRESHOLD_BITS)
     }
 }
 
-impl<'c, 'm> Open<DecryptionKey, RawCiphertext<'c>, RawPlaintext<'m>, Randomness> for Paillier {
-    fn open(dk: &DecryptionKey, c: RawCiphertext<'c>) -> (RawPlaintext<'m>, Randomness) {
-        Self::open(dk, &c)
+/// Modulus size from which decryption switches from the direct method to CRT.
+///
+/// Below this size the overhead of decomposing and recombining outweighs the savings of
+/// exponentiating modulo `p^2` and `q^2` in parallel. The `decryption_threshold`
+/// benchmarks put the crossover between 128 and 256 bit moduli:
+///
+/// | modulus  | direct  | CRT     |
+/// |----------|---------|---------|
+/// | 64 bit   | 1.6 µs  | 12 µs   |
+/// | 128 bit  | 5.3 µs  | 11 µs   |
+/// | 256 bit  | 32 µs   | 22 µs   |
+/// | 2048 bit | 12.4 ms | 3.3 ms  |
+pub const CRT_THRESHOLD_BITS: usize = 192;
+
+impl Paillier {
+    /// Decrypt using CRT if the modulus has at least `crt_threshold_bits` bits, and the
+    /// direct method `L(c^lambda mod n^2) * mu mod n` otherwise.
+    ///
+    /// Both methods give the same plaintext; `decrypt` uses `CRT_THRESHOLD_BITS`.
+    pub fn decrypt_with_threshold(
+        dk: &DecryptionKey,
+        c: &RawCiphertext,
+        crt_threshold_bits: usize,
+    ) -> RawPlaintext<'static> {
+        if dk.n.bit_length() < crt_threshold_bits {
+            decrypt_direct(dk, c)
+        } else {
+            decrypt_crt(dk, c)
+        }
     }
-}
 
-impl<'c, 'm> Open<DecryptionKey, &'c RawCiphertext<'c>, RawPlaintext<'m>, Randomness> for Paillier {
-    fn open(dk: &DecryptionKey, c: &'c RawCiphertext<'c>) -> (RawPlaintext<'m>, Randomness) {
-        let dk_n = &dk.p * &dk.q;
-        let dk_nn = &dk_n * &dk_n;
+    /// Decrypt all ciphertexts in parallel, returning the plaintexts in input order.
+    ///
+    /// The decryption key, including its CRT constants, is shared by all rayon worker
+    /// threads rather than recomputed per item. Work runs on the current rayon pool, so
+    /// calling this within `ThreadPool::install` limits it to that pool's threads.
+    pub fn decrypt_batch(dk: &DecryptionKey, cs: &[RawCiphertext]) -> Vec<RawPlaintext<'static>> {
+        cs.par_iter()
+            .map(|c| Self::decrypt_with_threshold(dk, c, CRT_THRESHOLD_BITS))
+            .collect()
+    }
 
-        let m = Self::decrypt(dk, c);
-        let gminv = (BigInt::one() - (m.0.borrow() as &BigInt) * &dk_n) % &dk_nn;
-        let rn = (c.0.borrow() as &BigInt * gminv) % &dk_nn;
-        let r = extract_nroot(dk, &rn);
-        (m, Randomness(r))
+    /// Decrypt as `decrypt_batch`, but handing ciphertexts to rayon in chunks of `chunk_size`.
+    ///
+    /// Larger chunks reduce scheduling overhead when every item is equally expensive,
+    /// smaller ones balance uneven workloads better. Panics if `chunk_size` is zero.
+    pub fn decrypt_batch_with_chunk_size(
+        dk: &DecryptionKey,
+        cs: &[RawCiphertext],
+        chunk_size: usize,
+    ) -> Vec<RawPlaintext<'static>> {
+        cs.par_chunks(chunk_size)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .map(|c| Self::decrypt_with_threshold(dk, c, CRT_THRESHOLD_BITS))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+            .concat()
+    }
+}
+
+/// Secret-derived intermediate of decryption.
+///
+/// With the `zeroize` feature this is overwritten when dropped. This is best effort only:
+/// the arithmetic backend may have left copies behind while computing the value, and
+/// freed memory of the previous value is not cleared.
+#[cfg(feature = "zeroize")]
+type SecretTemp = zeroize::Zeroizing<BigInt>;
+#[cfg(not(feature = "zeroize"))]
+type SecretTemp = BigInt;
+
+fn decrypt_direct(dk: &DecryptionKey, c: &RawCiphertext) -> RawPlaintext<'static> {
+    let u = SecretTemp::from(BigInt::mod_pow(c.0.borrow(), &dk.lambda, &dk.nn));
+    let lu = SecretTemp::from(l(&u, &dk.n));
+    let m = BigInt::mod_mul(&lu, &dk.mu, &dk.n);
+    RawPlaintext(Cow::Owned(m))
+}
+
+fn decrypt_crt(dk: &DecryptionKey, c: &RawCiphertext) -> RawPlaintext<'static> {
+    let (cp, cq) = crt_decompose(c.0.borrow(), &dk.pp, &dk.qq);
+    let (cp, cq) = (SecretTemp::from(cp), SecretTemp::from(cq));
+    // decrypt in parallel with respectively p and q
+    let (mp, mq) = join(
+        || {
+            // process using p
+            let dp = SecretTemp::from(BigInt::mod_pow(&cp, &dk.pminusone, &dk.pp));
+            let lp = SecretTemp::from(l(&dp, &dk.p));
+            BigInt::mod_mul(&lp, &dk.hp, &dk.p)
+        },
+        || {
+            // process using q
+            let dq = SecretTemp::from(BigInt::mod_pow(&cq, &dk.qminusone, &dk.qq));
+            let lq = SecretTemp::from(l(&dq, &dk.q));
+            BigInt::mod_mul(&lq, &dk.hq, &dk.q)
+        },
+    );
+    // perform CRT
+    let m = crt_recombine(mp, mq, &dk.p, &dk.q, &dk.pinv);
+    RawPlaintext(Cow::Owned(m))
+}
+
+impl<'c, 'm> Open<DecryptionKey, RawCiphertext<'c>, RawPlaintext<'m>, Randomness> for Paillier {
+    fn open(dk: &DecryptionKey, c: RawCiphertext<'c>) -> (RawPlaintext<'m>, Randomness) {
+        Self::open(dk, &c)
+    }
+}
+
+impl<'c, 'm> Open<DecryptionKey, &'c RawCiphertext<'c>, RawPlaintext<'m>, Randomness> for Paillier {
+    fn open(dk: &DecryptionKey, c: &'c RawCiphertext<'c>) -> (RawPlaintext<'m>, Randomness) {
+        Self::try_open(dk, c).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
@@ -400,7 +1447,7 @@ impl<'c1, 'c2, 'd> Add<EncryptionKey, RawCiphertext<'c1>, RawCiphertext<'c2>, Ra
         c1: RawCiphertext<'c1>,
         c2: RawCiphertext<'c2>,
     ) -> RawCiphertext<'d> {
-        let d = (c1.0.borrow() as &BigInt * c2.0.borrow() as &BigInt) % &ek.nn;
+        let d = BigInt::mod_mul(c1.0.borrow(), c2.0.borrow(), &ek.nn);
         RawCiphertext(Cow::Owned(d))
     }
 }
@@ -411,7 +1458,7 @@ impl<'c, 'm, 'd> Add<EncryptionKey, RawCiphertext<'c>, RawPlaintext<'m>, RawCiph
     fn add(ek: &EncryptionKey, c: RawCiphertext<'c>, m: RawPlaintext<'m>) -> RawCiphertext<'d> {
         let c1 = c.0.borrow() as &BigInt;
         let c2 = (m.0.borrow() as &BigInt * &ek.n + 1) % &ek.nn;
-        let d = (c1 * c2) % &ek.nn;
+        let d = BigInt::mod_mul(c1, &c2, &ek.nn);
         RawCiphertext(Cow::Owned(d))
     }
 }
@@ -422,7 +1469,7 @@ impl<'c, 'm, 'd> Add<EncryptionKey, RawPlaintext<'m>, RawCiphertext<'c>, RawCiph
     fn add(ek: &EncryptionKey, m: RawPlaintext<'m>, c: RawCiphertext<'c>) -> RawCiphertext<'d> {
         let c1 = (m.0.borrow() as &BigInt * &ek.n + 1) % &ek.nn;
         let c2 = c.0.borrow() as &BigInt;
-        let d = (c1 * c2) % &ek.nn;
+        let d = BigInt::mod_mul(&c1, c2, &ek.nn);
         RawCiphertext(Cow::Owned(d))
     }
 }
@@ -451,7 +1498,149 @@ impl<'c, 'm, 'd> Mul<EncryptionKey, RawPlaintext<'m>, RawCiphertext<'c>, RawCiph
     }
 }
 
-fn h(p: &BigInt, pp: &BigInt, n: &BigInt) -> BigInt {
+/// Negation as the inverse of `c` modulo `n^2`.
+///
+/// Computing `c^(n-1)` gives an encryption of the same plaintext, but the inverse is
+/// faster at every size measured: about 2x for 64 bit moduli, 13x for 256 bit and 460x
+/// for 2048 bit (38 µs against 17 ms, see `bench_modinv_nn` and `bench_modpow_nn`).
+/// Exponentiation is only used as a fallback for ciphertexts that are, incorrectly,
+/// not invertible.
+impl<'c, 'd> Neg<EncryptionKey, RawCiphertext<'c>, RawCiphertext<'d>> for Paillier {
+    fn neg(ek: &EncryptionKey, c: RawCiphertext<'c>) -> RawCiphertext<'d> {
+        let c: &BigInt = c.0.borrow();
+        let d = BigInt::mod_inv(c, &ek.nn)
+            .unwrap_or_else(|| BigInt::mod_pow(c, &(&ek.n - BigInt::one()), &ek.nn));
+        RawCiphertext(Cow::Owned(d))
+    }
+}
+
+impl<'k, T> WithKey<'k, T> {
+    /// Pair `value` with `ek`, tagging it with the key's fingerprint.
+    pub fn new(ek: &'k EncryptionKey, value: T) -> Self {
+        WithKey {
+            ek,
+            value,
+            fingerprint: ek.fingerprint(),
+        }
+    }
+
+    /// Fingerprint of the key the value was wrapped with.
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    fn check_key(&self) -> Result<(), Error> {
+        if self.fingerprint != self.ek.fingerprint() {
+            return Err(Error::ModulusMismatch);
+        }
+        Ok(())
+    }
+}
+
+impl<'k, 'c> WithKey<'k, RawCiphertext<'c>> {
+    /// Homomorphically add `rhs`, failing if the two ciphertexts are under different keys.
+    pub fn checked_add(
+        &self,
+        rhs: &WithKey<'_, RawCiphertext<'_>>,
+    ) -> Result<WithKey<'k, RawCiphertext<'static>>, Error> {
+        if self.fingerprint != rhs.fingerprint {
+            return Err(Error::ModulusMismatch);
+        }
+        self.check_key()?;
+        rhs.check_key()?;
+        let value = Paillier::add(
+            self.ek,
+            RawCiphertext::from(self.value.0.borrow() as &BigInt),
+            RawCiphertext::from(rhs.value.0.borrow() as &BigInt),
+        );
+        Ok(WithKey {
+            ek: self.ek,
+            value,
+            fingerprint: self.fingerprint,
+        })
+    }
+
+    /// Homomorphically multiply by `m`, failing if the ciphertext was tagged with another key.
+    pub fn checked_mul(
+        &self,
+        m: &RawPlaintext<'_>,
+    ) -> Result<WithKey<'k, RawCiphertext<'static>>, Error> {
+        self.check_key()?;
+        let value = Paillier::mul(
+            self.ek,
+            RawCiphertext::from(self.value.0.borrow() as &BigInt),
+            RawPlaintext::from(m.0.borrow() as &BigInt),
+        );
+        Ok(WithKey {
+            ek: self.ek,
+            value,
+            fingerprint: self.fingerprint,
+        })
+    }
+}
+
+macro_rules! impl_ciphertext_add {
+    ([$($lt:lifetime),*], $lhs:ty, $rhs:ty) => {
+        impl<'k, $($lt),*> ops::Add<$rhs> for $lhs {
+            type Output = WithKey<'k, RawCiphertext<'static>>;
+
+            fn add(self, rhs: $rhs) -> Self::Output {
+                self.checked_add(&rhs).unwrap_or_else(|e| panic!("{}", e))
+            }
+        }
+    };
+}
+
+impl_ciphertext_add!(['c1, 'c2], WithKey<'k, RawCiphertext<'c1>>, WithKey<'k, RawCiphertext<'c2>>);
+impl_ciphertext_add!(['c1, 'c2, 'b], WithKey<'k, RawCiphertext<'c1>>, &'b WithKey<'k, RawCiphertext<'c2>>);
+impl_ciphertext_add!(['c1, 'c2, 'a], &'a WithKey<'k, RawCiphertext<'c1>>, WithKey<'k, RawCiphertext<'c2>>);
+impl_ciphertext_add!(['c1, 'c2, 'a, 'b], &'a WithKey<'k, RawCiphertext<'c1>>, &'b WithKey<'k, RawCiphertext<'c2>>);
+
+macro_rules! impl_ciphertext_mul {
+    ([$($lt:lifetime),*], $lhs:ty, $rhs:ty) => {
+        impl<'k, $($lt),*> ops::Mul<$rhs> for $lhs {
+            type Output = WithKey<'k, RawCiphertext<'static>>;
+
+            fn mul(self, rhs: $rhs) -> Self::Output {
+                let m = RawPlaintext::from(rhs.0.borrow() as &BigInt);
+                self.checked_mul(&m).unwrap_or_else(|e| panic!("{}", e))
+            }
+        }
+    };
+}
+
+impl_ciphertext_mul!(['c, 'm], WithKey<'k, RawCiphertext<'c>>, RawPlaintext<'m>);
+impl_ciphertext_mul!(['c, 'm, 'b], WithKey<'k, RawCiphertext<'c>>, &'b RawPlaintext<'m>);
+impl_ciphertext_mul!(['c, 'm, 'a], &'a WithKey<'k, RawCiphertext<'c>>, RawPlaintext<'m>);
+impl_ciphertext_mul!(['c, 'm, 'a, 'b], &'a WithKey<'k, RawCiphertext<'c>>, &'b RawPlaintext<'m>);
+
+/// Big-endian bytes of `x`, left-padded with zeros to at least `width` bytes.
+fn to_padded_bytes(x: &BigInt, width: usize) -> Vec<u8> {
+    let bytes = x.to_bytes();
+    let mut padded = vec![0u8; width.saturating_sub(bytes.len())];
+    padded.extend(bytes);
+    padded
+}
+
+/// 64-bit FNV-1a hash of the big-endian bytes of `n`.
+fn fingerprint(n: &BigInt) -> u64 {
+    n.to_bytes()
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+fn bigint_from_i64(x: i64) -> BigInt {
+    let magnitude = BigInt::from(x.unsigned_abs());
+    if x < 0 {
+        BigInt::zero() - magnitude
+    } else {
+        magnitude
+    }
+}
+
+fn h(p: &BigInt, pp: &BigInt, n: &BigInt) -> Option<BigInt> {
     // here we assume:
     //  - p \in {P, Q}
     //  - n = P * Q
@@ -462,7 +1651,7 @@ fn h(p: &BigInt, pp: &BigInt, n: &BigInt) -> BigInt {
     // compute L_p(.)
     let lp = l(&gp, p);
     // compute L_p(.)^{-1}
-    BigInt::mod_inv(&lp, p).unwrap()
+    BigInt::mod_inv(&lp, p)
 }
 
 fn l(u: &BigInt, n: &BigInt) -> BigInt {
@@ -496,22 +1685,64 @@ where
 }
 
 /// Extract randomness component of a zero ciphertext.
-pub fn extract_nroot(dk: &DecryptionKey, z: &BigInt) -> BigInt {
-    let dk_n = &dk.p * &dk.q;
+///
+/// Fails if `z` is not coprime to `n`, in which case it has no `n`-th root, or if
+/// `n` has no inverse modulo `phi(n)` for a malformed key.
+///
+/// With the `nroot-blinding` feature the exponentiation with the secret exponent is
+/// performed on `z * t^n` for a fresh random `t`, which is divided out again afterwards,
+/// so that side channels of the exponentiation relate to a random value instead of `z`.
+pub fn extract_nroot(dk: &DecryptionKey, z: &BigInt) -> Result<BigInt, Error> {
+    if cfg!(feature = "nroot-blinding") {
+        extract_nroot_blinded(dk, z)
+    } else {
+        extract_nroot_unblinded(dk, z)
+    }
+}
 
-    let dk_pinv = BigInt::mod_inv(&dk.p, &dk.q).unwrap();
-    let dk_qminusone = &dk.q - BigInt::one();
-    let dk_pminusone = &dk.p - BigInt::one();
+fn extract_nroot_blinded(dk: &DecryptionKey, z: &BigInt) -> Result<BigInt, Error> {
+    if BigInt::gcd(z, &dk.n) != BigInt::one() {
+        return Err(Error::NotCoprime);
+    }
+    let (t, t_inv) = loop {
+        let t = BigInt::sample_below(&dk.n);
+        if let Some(t_inv) = BigInt::mod_inv(&t, &dk.n) {
+            break (t, t_inv);
+        }
+    };
+    let blinded = BigInt::mod_mul(z, &BigInt::mod_pow(&t, &dk.n, &dk.n), &dk.n);
+    let root = extract_nroot_unblinded(dk, &blinded)?;
+    Ok(BigInt::mod_mul(&root, &t_inv, &dk.n))
+}
 
-    let dk_phi = &dk_pminusone * &dk_qminusone;
-    let dk_dn = BigInt::mod_inv(&dk_n, &dk_phi).unwrap();
-    let (dk_dp, dk_dq) = crt_decompose(dk_dn, &dk_pminusone, &dk_qminusone);
+fn extract_nroot_unblinded(dk: &DecryptionKey, z: &BigInt) -> Result<BigInt, Error> {
+    if BigInt::gcd(z, &dk.n) != BigInt::one() {
+        return Err(Error::NotCoprime);
+    }
     let (zp, zq) = crt_decompose(z, &dk.p, &dk.q);
 
-    let rp = BigInt::mod_pow(&zp, &dk_dp, &dk.p);
-    let rq = BigInt::mod_pow(&zq, &dk_dq, &dk.q);
+    let rp = BigInt::mod_pow(&zp, &dk.dp, &dk.p);
+    let rq = BigInt::mod_pow(&zq, &dk.dq, &dk.q);
+
+    Ok(crt_recombine(rp, rq, &dk.p, &dk.q, &dk.pinv))
+}
+
+pub mod math {
+    //! Number theoretic helpers.
+
+    use curv::arithmetic::traits::*;
+
+    use crate::BigInt;
 
-    crt_recombine(rp, rq, &dk.p, &dk.q, &dk_pinv)
+    /// Carmichael function `lcm(p - 1, q - 1)` of `n = p * q` for distinct primes `p` and `q`.
+    ///
+    /// This is the exponent used for decryption; it divides, and is usually much
+    /// smaller than, Euler's totient `(p - 1)(q - 1)`.
+    pub fn carmichael(p: &BigInt, q: &BigInt) -> BigInt {
+        let pminusone = p - BigInt::one();
+        let qminusone = q - BigInt::one();
+        &pminusone * &qminusone / BigInt::gcd(&pminusone, &qminusone)
+    }
 }
 
 #[cfg(test)]
@@ -538,16 +1769,797 @@ mod tests {
         assert_eq!(recovered_p, p);
     }
 
+    #[test]
+    fn test_concurrent_encryption() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<EncryptionKey>();
+        assert_send_sync::<DecryptionKey>();
+
+        let (ek, dk) = test_keypair().keys();
+        let ek = std::sync::Arc::new(ek);
+
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let ek = ek.clone();
+                std::thread::spawn(move || -> RawCiphertext {
+                    Paillier::encrypt(&*ek, RawPlaintext::from(BigInt::from(i)))
+                })
+            })
+            .collect();
+        for (i, handle) in handles.into_iter().enumerate() {
+            let m = Paillier::decrypt(&dk, handle.join().unwrap());
+            assert_eq!(m, BigInt::from(i as u32).into());
+        }
+    }
+
+    #[cfg(feature = "unsafe-deterministic-encryption")]
+    #[test]
+    fn test_deterministic_encryption() {
+        let (ek, dk) = test_keypair().keys();
+        let key = BigInt::from(1234);
+
+        let m = || RawPlaintext::from(BigInt::from(10));
+        let c1 = Paillier::encrypt_deterministic_UNSAFE(&ek, &key, m());
+        let c2 = Paillier::encrypt_deterministic_UNSAFE(&ek, &key, m());
+        assert_eq!(c1, c2);
+        assert_eq!(Paillier::decrypt(&dk, &c1), m());
+
+        let c3 = Paillier::encrypt_deterministic_UNSAFE(&ek, &BigInt::from(5678), m());
+        assert_ne!(c1, c3);
+        let c4 =
+            Paillier::encrypt_deterministic_UNSAFE(&ek, &key, RawPlaintext::from(BigInt::from(11)));
+        assert_ne!(c1, c4);
+    }
+
+    #[test]
+    fn test_carmichael() {
+        // lcm(10, 12) = 60, whereas (p - 1)(q - 1) = 120
+        assert_eq!(
+            math::carmichael(&BigInt::from(11), &BigInt::from(13)),
+            BigInt::from(60)
+        );
+        assert_eq!(
+            math::carmichael(&BigInt::from(5), &BigInt::from(7)),
+            BigInt::from(12)
+        );
+
+        let keypair = test_keypair();
+        let dk = keypair.secret_key();
+        assert_eq!(dk.lambda(), &math::carmichael(&keypair.p, &keypair.q));
+
+        // both decryption methods agree on the hand-computable key n = 143
+        let (ek, dk) = Keypair::from((&BigInt::from(11), &BigInt::from(13))).keys();
+        let m = RawPlaintext::from(BigInt::from(100));
+        let c = Paillier::encrypt(&ek, m.clone());
+        assert_eq!(Paillier::decrypt_with_threshold(&dk, &c, usize::MAX), m);
+        assert_eq!(Paillier::decrypt_with_threshold(&dk, &c, 0), m);
+    }
+
+    #[test]
+    fn test_lambda() {
+        let (ek, dk) = test_keypair().keys();
+
+        let phi = &dk.pminusone * &dk.qminusone;
+        assert_eq!(phi.modulus(dk.lambda()), BigInt::zero());
+
+        let g = &ek.n + BigInt::one();
+        assert_eq!(BigInt::mod_pow(&g, dk.lambda(), &ek.n), BigInt::one());
+        let r = Randomness::sample(&ek);
+        assert_eq!(BigInt::mod_pow(&r.0, dk.lambda(), &ek.n), BigInt::one());
+    }
+
+    #[test]
+    fn test_mul_and_share() {
+        let (ek, dk) = test_keypair().keys();
+        let mut rng = rand::rngs::OsRng::new().unwrap();
+
+        let a = BigInt::from(10);
+        let b = BigInt::from(20);
+        let c_a = Paillier::encrypt(&ek, RawPlaintext::from(&a));
+
+        let (c, beta) = Paillier::mul_and_share(&ek, &c_a, &b, &mut rng);
+        let alpha: BigInt = Paillier::decrypt(&dk, c).into();
+        assert_ne!(alpha, BigInt::from(200));
+        assert_eq!(BigInt::mod_add(&alpha, &beta, &ek.n), BigInt::from(200));
+    }
+
+    #[test]
+    fn test_eval_poly() {
+        let (ek, dk) = test_keypair().keys();
+
+        let coefficients: Vec<RawCiphertext> = [1, 2, 3]
+            .iter()
+            .map(|a| Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(*a))))
+            .collect();
+
+        let c = Paillier::eval_poly(&ek, &coefficients, 2);
+        assert_eq!(Paillier::decrypt(&dk, c), BigInt::from(17).into());
+
+        // 1 - 2 * 2 + 3 * 4 = 9
+        let c = Paillier::eval_poly(&ek, &coefficients, -2);
+        assert_eq!(Paillier::decrypt(&dk, c), BigInt::from(9).into());
+
+        // 1 - 2 * 1 + 0 = -1
+        let c = Paillier::eval_poly(&ek, &coefficients[..2], -1);
+        let m: BigInt = Paillier::decrypt(&dk, c).into();
+        assert_eq!(m, &ek.n - BigInt::one());
+
+        let c = Paillier::eval_poly(&ek, &[], 2);
+        assert_eq!(Paillier::decrypt(&dk, c), BigInt::zero().into());
+    }
+
+    #[test]
+    fn test_gm_closed_form() {
+        // encryption computes g^m as 1 + m * n, valid for the generator g = n + 1 used throughout
+        let (ek, _) = test_keypair().keys();
+        let g = &ek.n + BigInt::one();
+        for m in [BigInt::zero(), BigInt::from(10), &ek.n - BigInt::one()].iter() {
+            let c = Paillier::encrypt_trivial(&ek, RawPlaintext::from(m));
+            assert_eq!(c, RawCiphertext::from(BigInt::mod_pow(&g, m, &ek.nn)));
+        }
+    }
+
+    #[test]
+    fn test_encrypt_trivial() {
+        let (ek, dk) = test_keypair().keys();
+        let other_party_ek: EncryptionKey = ek.to_string().parse().unwrap();
+
+        let c = Paillier::encrypt_trivial(&ek, RawPlaintext::from(BigInt::from(1)));
+        let c_other =
+            Paillier::encrypt_trivial(&other_party_ek, RawPlaintext::from(BigInt::from(1)));
+        assert_eq!(c, c_other);
+        assert_eq!(
+            Paillier::decrypt(&dk, &c),
+            RawPlaintext::from(BigInt::from(1))
+        );
+
+        let zero = Paillier::encrypt_trivial(&ek, RawPlaintext::from(BigInt::zero()));
+        assert_eq!(zero, RawCiphertext::from(BigInt::one()));
+
+        let minus_one = Paillier::encrypt_trivial(&ek, RawPlaintext::from(BigInt::from(-1)));
+        let sum = Paillier::add(&ek, c, minus_one);
+        assert_eq!(
+            Paillier::decrypt(&dk, sum),
+            RawPlaintext::from(BigInt::zero())
+        );
+    }
+
+    #[test]
+    fn test_set_membership() {
+        let (ek, dk) = test_keypair().keys();
+
+        let coefficients = Paillier::encode_set_polynomial(&[3, -5, 7]).unwrap();
+        assert_eq!(coefficients, vec![105, -29, -5, 1]);
+        let encrypted: Vec<RawCiphertext> = coefficients
+            .iter()
+            .map(|a| {
+                let a = bigint_from_i64(*a).modulus(&ek.n);
+                Paillier::encrypt(&ek, RawPlaintext::from(a))
+            })
+            .collect();
+
+        let member = Paillier::eval_poly(&ek, &encrypted, -5);
+        assert_eq!(
+            Paillier::decrypt(&dk, member),
+            RawPlaintext::from(BigInt::zero())
+        );
+
+        let non_member = Paillier::eval_poly(&ek, &encrypted, 4);
+        assert_eq!(
+            Paillier::decrypt(&dk, non_member),
+            RawPlaintext::from(&ek.n - BigInt::from(27))
+        );
+
+        assert_eq!(Paillier::encode_set_polynomial(&[]), Some(vec![1]));
+        assert_eq!(Paillier::encode_set_polynomial(&[i64::MAX, 2]), None);
+    }
+
+    #[test]
+    fn test_add_plaintext_vec() {
+        let (ek, dk) = test_keypair().keys();
+
+        let x: Vec<RawCiphertext> = [3u32, 5, 7]
+            .iter()
+            .map(|v| Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(*v))))
+            .collect();
+
+        let y = Paillier::add_plaintext_vec(&ek, &x, &[10, -5, 0]).unwrap();
+        let y: Vec<BigInt> = y
+            .into_iter()
+            .map(|c| Paillier::decrypt(&dk, c).into())
+            .collect();
+        assert_eq!(y, vec![BigInt::from(13), BigInt::zero(), BigInt::from(7)]);
+
+        assert_eq!(
+            Paillier::add_plaintext_vec(&ek, &x, &[1, 2]),
+            Err(Error::DimensionMismatch)
+        );
+    }
+
+    #[test]
+    fn test_iterator_adapters() {
+        let (ek, dk) = test_keypair().keys();
+
+        let cs: Vec<RawCiphertext> = [3u32, 5, 7]
+            .iter()
+            .map(|v| Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(*v))))
+            .collect();
+        let ms: Vec<RawPlaintext> = [2u32, 0, 10]
+            .iter()
+            .map(|v| RawPlaintext::from(BigInt::from(*v)))
+            .collect();
+
+        let sum = cs.iter().homomorphic_sum(&ek);
+        assert_eq!(
+            Paillier::decrypt(&dk, sum),
+            RawPlaintext::from(BigInt::from(15))
+        );
+
+        let product = cs.iter().zip(&ms).homomorphic_product_with_scalars(&ek);
+        assert_eq!(
+            Paillier::decrypt(&dk, product),
+            RawPlaintext::from(BigInt::from(76))
+        );
+
+        let empty = cs[..0].iter().homomorphic_sum(&ek);
+        assert_eq!(
+            Paillier::decrypt(&dk, empty),
+            RawPlaintext::from(BigInt::zero())
+        );
+    }
+
+    #[test]
+    fn test_matvec() {
+        let (ek, dk) = test_keypair().keys();
+
+        let x: Vec<RawCiphertext> = [3u32, 5, 7]
+            .iter()
+            .map(|v| Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(*v))))
+            .collect();
+        let w = vec![vec![1, 2, 3], vec![0, -1, 2], vec![4, 0, 0]];
+
+        let y = Paillier::matvec(&ek, &w, &x).unwrap();
+        let y: Vec<BigInt> = y
+            .into_iter()
+            .map(|c| Paillier::decrypt(&dk, c).into())
+            .collect();
+        assert_eq!(y, vec![BigInt::from(34), BigInt::from(9), BigInt::from(12)]);
+
+        let ragged = vec![vec![1, 2, 3], vec![1, 2]];
+        assert_eq!(
+            Paillier::matvec(&ek, &ragged, &x),
+            Err(Error::DimensionMismatch)
+        );
+
+        let negative = Paillier::linear_combination(&ek, &[-2, 1], &x[..2]).unwrap();
+        let negative: BigInt = Paillier::decrypt(&dk, negative).into();
+        assert_eq!(negative, &ek.n - BigInt::one());
+    }
+
+    #[test]
+    fn test_encrypt_random() {
+        let (ek, dk) = test_keypair().keys();
+        let mut rng = rand::rngs::OsRng::new().unwrap();
+
+        let (c, m) = Paillier::encrypt_random(&ek, 128, &mut rng).unwrap();
+        assert!(m < BigInt::one() << 128);
+        assert_eq!(Paillier::decrypt(&dk, c), RawPlaintext::from(m));
+
+        let bits = ek.n.bit_length();
+        assert!(Paillier::encrypt_random(&ek, bits - 1, &mut rng).is_ok());
+        assert_eq!(
+            Paillier::encrypt_random(&ek, bits, &mut rng),
+            Err(Error::BoundExceeded)
+        );
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_decrypt_with_zeroize() {
+        let (ek, dk) = test_keypair().keys();
+
+        let c = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(10)));
+        let direct = Paillier::decrypt_with_threshold(&dk, &c, usize::MAX);
+        let crt = Paillier::decrypt_with_threshold(&dk, &c, 0);
+        assert_eq!(direct, RawPlaintext::from(BigInt::from(10)));
+        assert_eq!(crt, RawPlaintext::from(BigInt::from(10)));
+    }
+
+    #[test]
+    fn test_randomness_is_unbiased() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        // reducing 64 random bits modulo n = 3 * 2^62 + 1 would make values below 2^62
+        // twice as likely as the rest
+        let quarter = BigInt::one() << 62;
+        let ek = EncryptionKey::from(&(&quarter * BigInt::from(3) + BigInt::one()));
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let mut buckets = [0usize; 3];
+        for _ in 0..3000 {
+            let r = Randomness::sample_with_rng(&ek, &mut rng);
+            assert!(ek.is_valid_randomness(&r.0));
+            let bucket: u64 = (&r.0 / &quarter).to_str_radix(10).parse().unwrap();
+            buckets[bucket.min(2) as usize] += 1;
+        }
+        for count in buckets.iter() {
+            assert!(*count > 850 && *count < 1150, "buckets {:?}", buckets);
+        }
+    }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        let (ek, dk) = test_keypair().keys();
+        let c1 = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(10)));
+        let c2 = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(20)));
+
+        let mut sum: RawCiphertext = Paillier::add(&ek, c1, c2);
+        let before = sum.clone();
+        sum.shrink_to_fit();
+        assert_eq!(sum, before);
+        assert_eq!(
+            Paillier::decrypt(&dk, sum),
+            RawPlaintext::from(BigInt::from(30))
+        );
+    }
+
+    #[test]
+    fn test_decrypt_batch() {
+        let (ek, dk) = test_keypair().keys();
+
+        let ms: Vec<BigInt> = (0..8u32).map(|i| BigInt::from(i * 1000)).collect();
+        let cs: Vec<RawCiphertext> = ms
+            .iter()
+            .map(|m| Paillier::encrypt(&ek, RawPlaintext::from(m)))
+            .collect();
+
+        let decrypted: Vec<BigInt> = Paillier::decrypt_batch(&dk, &cs)
+            .into_iter()
+            .map(BigInt::from)
+            .collect();
+        assert_eq!(decrypted, ms);
+        assert!(Paillier::decrypt_batch(&dk, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_decrypt_batch_with_chunk_size() {
+        let (ek, dk) = test_keypair().keys();
+
+        let cs: Vec<RawCiphertext> = (0..7u32)
+            .map(|i| Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(i))))
+            .collect();
+        let expected = Paillier::decrypt_batch(&dk, &cs);
+        for chunk_size in [1, 2, 3, 7, 100].iter() {
+            let ms = Paillier::decrypt_batch_with_chunk_size(&dk, &cs, *chunk_size);
+            assert_eq!(ms, expected);
+        }
+    }
+
+    #[test]
+    fn test_decrypt_batch_in_thread_pool() {
+        let (ek, dk) = test_keypair().keys();
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap();
+
+        let cs: Vec<RawCiphertext> = (0..4u32)
+            .map(|i| Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(i))))
+            .collect();
+        let (threads, ms) = pool.install(|| {
+            (
+                rayon::current_num_threads(),
+                Paillier::decrypt_batch(&dk, &cs),
+            )
+        });
+        assert_eq!(threads, 2);
+        let ms: Vec<BigInt> = ms.into_iter().map(BigInt::from).collect();
+        assert_eq!(ms, (0..4u32).map(BigInt::from).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_decrypt_with_threshold() {
+        let small = Paillier::keypair_unsafe_small(CRT_THRESHOLD_BITS);
+        let large = Paillier::keypair_unsafe_small(CRT_THRESHOLD_BITS + 64);
+        for keypair in [small, large, test_keypair()] {
+            let (ek, dk) = keypair.keys();
+            let bits = dk.n.bit_length();
+
+            let m = RawPlaintext::from(BigInt::sample_below(&ek.n));
+            let c = Paillier::encrypt(&ek, m.clone());
+            let direct = Paillier::decrypt_with_threshold(&dk, &c, bits + 1);
+            let crt = Paillier::decrypt_with_threshold(&dk, &c, bits);
+            assert_eq!(direct, m);
+            assert_eq!(crt, m);
+            assert_eq!(Paillier::decrypt(&dk, &c), m);
+        }
+    }
+
+    #[test]
+    fn test_conditional_rerandomize_zero() {
+        let (ek, dk) = test_keypair().keys();
+
+        let c = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(10)));
+
+        let kept = dk.conditional_rerandomize_zero(&ek, &c, true);
+        assert_ne!(kept, c);
+        assert_eq!(Paillier::decrypt(&dk, &kept), BigInt::from(10).into());
+
+        let zeroed = dk.conditional_rerandomize_zero(&ek, &c, false);
+        assert_ne!(zeroed, RawCiphertext::from(BigInt::one()));
+        assert_eq!(Paillier::decrypt(&dk, &zeroed), BigInt::zero().into());
+
+        let zeroed_again = dk.conditional_rerandomize_zero(&ek, &c, false);
+        assert_ne!(zeroed, zeroed_again);
+    }
+
+    #[test]
+    fn test_security_bits() {
+        let security_bits = |bits: usize| {
+            let n = (BigInt::one() << bits) - BigInt::one();
+            EncryptionKey::from(&n).security_bits()
+        };
+        assert_eq!(security_bits(1024), 80);
+        assert_eq!(security_bits(2048), 112);
+        assert_eq!(security_bits(2047), 112);
+        assert_eq!(security_bits(3072), 128);
+        assert_eq!(security_bits(7680), 192);
+        assert_eq!(security_bits(15360), 256);
+        assert_eq!(security_bits(20000), 256);
+        assert_eq!(security_bits(2560), 120);
+        assert_eq!(security_bits(512), 40);
+
+        assert_eq!(test_keypair().public_key().security_bits(), 112);
+    }
+
+    #[test]
+    fn test_into_public() {
+        let (ek, dk) = test_keypair().keys();
+        let c = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(10)));
+
+        let public = dk.clone().into_public();
+        assert_eq!(public, ek);
+        let c2 = Paillier::encrypt(&public, RawPlaintext::from(BigInt::from(20)));
+        let sum = Paillier::add(&public, c, c2);
+        assert_eq!(
+            Paillier::decrypt(&dk, sum),
+            RawPlaintext::from(BigInt::from(30))
+        );
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_scrub() {
+        let (_, mut dk) = test_keypair().keys();
+        dk.scrub();
+        assert_eq!(dk.p, BigInt::zero());
+        assert_eq!(dk.lambda, BigInt::zero());
+        assert_eq!(dk.mu, BigInt::zero());
+        assert_ne!(dk.n, BigInt::zero());
+    }
+
+    #[test]
+    fn test_max_additions() {
+        // 2^16 < n, so 2^8 values below 2^8 sum to at most 2^16 - 2^8
+        let ek = EncryptionKey::from(&BigInt::from(65537));
+        assert_eq!(ek.max_additions(8), 8);
+        assert_eq!(ek.max_additions(16), 0);
+        assert_eq!(ek.max_additions(20), 0);
+
+        for degenerate in &[BigInt::zero(), BigInt::one()] {
+            let ek = EncryptionKey::from(degenerate);
+            assert_eq!(ek.max_additions(0), 0);
+        }
+
+        let (ek, _) = test_keypair().keys();
+        assert_eq!(ek.max_additions(64), ek.n.bit_length() as u64 - 65);
+    }
+
+    #[test]
+    fn test_checked_add_mul() {
+        let (ek, dk) = test_keypair().keys();
+        let c1 = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(10)));
+        let c2 = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(20)));
+        let m = RawPlaintext::from(BigInt::from(3));
+
+        let sum = Paillier::checked_add(&ek, &c1, &c2).unwrap();
+        assert_eq!(
+            Paillier::decrypt(&dk, sum),
+            RawPlaintext::from(BigInt::from(30))
+        );
+        let product = Paillier::checked_mul(&ek, &c1, &m).unwrap();
+        assert_eq!(
+            Paillier::decrypt(&dk, product),
+            RawPlaintext::from(BigInt::from(30))
+        );
+
+        // multiple of the first prime, within range but not a unit
+        let crafted = RawCiphertext::from(BigInt::mod_mul(&c1.0, &dk.p, &ek.nn));
+        assert_eq!(crafted.validate(&ek), Ok(()));
+        assert_eq!(
+            Paillier::checked_add(&ek, &c1, &crafted),
+            Err(Error::NotCoprime)
+        );
+        assert_eq!(
+            Paillier::checked_add(&ek, &crafted, &c2),
+            Err(Error::NotCoprime)
+        );
+        assert_eq!(
+            Paillier::checked_mul(&ek, &crafted, &m),
+            Err(Error::NotCoprime)
+        );
+        let out_of_range = RawCiphertext::from(&ek.nn + BigInt::one());
+        assert_eq!(
+            Paillier::checked_mul(&ek, &out_of_range, &m),
+            Err(Error::CiphertextOutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_decrypt_is_zero() {
+        let (ek, dk) = test_keypair().keys();
+
+        let zero = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::zero()));
+        assert!(dk.decrypt_is_zero(&zero));
+        assert!(dk.decrypt_is_zero(&RawCiphertext::from(BigInt::one())));
+
+        for m in [BigInt::one(), BigInt::from(256), &ek.n - BigInt::one()].iter() {
+            let c = Paillier::encrypt(&ek, RawPlaintext::from(m));
+            assert!(!dk.decrypt_is_zero(&c));
+        }
+    }
+
+    #[test]
+    fn test_key_id() {
+        let (ek, dk) = test_keypair().keys();
+        let (other_ek, _) = Paillier::keypair_unsafe_small(128).keys();
+
+        assert_eq!(ek.key_id(), dk.encryption_key().key_id());
+        assert_eq!(ek.key_id(), ek.clone().key_id());
+        assert_ne!(ek.key_id(), other_ek.key_id());
+    }
+
+    #[test]
+    fn test_interoperable_keys() {
+        let (ek, dk) = test_keypair().keys();
+        let (other_ek, other_dk) = Paillier::keypair_unsafe_small(128).keys();
+
+        assert!(ek.interoperable_with(&ek.clone()));
+        assert!(ek.interoperable_with(&dk.encryption_key()));
+        assert!(!ek.interoperable_with(&other_ek));
+
+        let imported: DecryptionKey =
+            serde_json::from_str(&serde_json::to_string(&dk).unwrap()).unwrap();
+        assert!(dk.functionally_equal(&imported));
+        assert!(!dk.functionally_equal(&other_dk));
+
+        let mut corrupted = dk.clone();
+        corrupted.hp = &corrupted.hp + BigInt::one();
+        assert!(!dk.functionally_equal(&corrupted));
+    }
+
+    #[test]
+    fn test_named_key_accessors() {
+        let keypair = test_keypair();
+        let (ek, dk) = keypair.keys();
+        assert_eq!(keypair.public_key(), ek);
+        assert_eq!(keypair.secret_key(), dk);
+        assert_eq!(keypair.public_key().n, &keypair.p * &keypair.q);
+    }
+
+    #[test]
+    fn test_encrypt_strict() {
+        let (ek, dk) = test_keypair().keys();
+
+        let c = Paillier::encrypt_strict(&ek, RawPlaintext::from(BigInt::from(10)), true).unwrap();
+        assert_eq!(Paillier::decrypt(&dk, c), BigInt::from(10).into());
+
+        let c = Paillier::encrypt_strict(&ek, RawPlaintext::from(BigInt::zero()), false).unwrap();
+        assert_eq!(Paillier::decrypt(&dk, c), BigInt::zero().into());
+
+        let result = Paillier::encrypt_strict(&ek, RawPlaintext::from(BigInt::zero()), true);
+        assert_eq!(result, Err(Error::ZeroPlaintext));
+
+        let result = Paillier::encrypt_strict(&ek, RawPlaintext::from(&ek.n), false);
+        assert_eq!(result, Err(Error::PlaintextEqualsModulus));
+
+        let result = Paillier::encrypt_strict(&ek, RawPlaintext::from(&ek.n + 1), false);
+        assert_eq!(result, Err(Error::PlaintextOutOfRange));
+
+        let result = Paillier::encrypt_strict(&ek, RawPlaintext::from(BigInt::from(-1)), false);
+        assert_eq!(result, Err(Error::PlaintextOutOfRange));
+    }
+
+    #[test]
+    fn test_max_plaintext() {
+        let (ek, dk) = test_keypair().keys();
+        assert_eq!(ek.max_plaintext(), &ek.n - BigInt::one());
+
+        let m = RawPlaintext::from(ek.max_plaintext());
+        let c = Paillier::encrypt_strict(&ek, m.clone(), true).unwrap();
+        assert_eq!(Paillier::decrypt(&dk, c), m);
+    }
+
+    #[test]
+    fn test_encryption_key_from_decryption_key() {
+        let (ek, dk) = test_keypair().keys();
+        let derived = dk.encryption_key();
+        assert_eq!(derived, ek);
+
+        let c = Paillier::encrypt(&derived, RawPlaintext::from(BigInt::from(10)));
+        assert_eq!(Paillier::decrypt(&dk, c), BigInt::from(10).into());
+    }
+
+    #[test]
+    fn test_decryption_uses_cached_values() {
+        let (ek, dk) = test_keypair().keys();
+        assert_eq!(BigInt::mod_mul(dk.lambda(), dk.mu(), &dk.n), BigInt::one());
+
+        let m = RawPlaintext::from(BigInt::from(10));
+        let c = Paillier::encrypt(&ek, m.clone());
+
+        // decryption must read the precomputed values rather than derive them from p and q,
+        // so corrupting them changes the result of the corresponding path
+        let mut corrupted = dk.clone();
+        corrupted.mu = BigInt::one();
+        assert_ne!(
+            Paillier::decrypt_with_threshold(&corrupted, &c, usize::MAX),
+            m
+        );
+        assert_eq!(Paillier::decrypt_with_threshold(&corrupted, &c, 0), m);
+
+        let mut corrupted = dk.clone();
+        corrupted.hp = BigInt::one();
+        corrupted.hq = BigInt::one();
+        assert_ne!(Paillier::decrypt_with_threshold(&corrupted, &c, 0), m);
+        assert_eq!(
+            Paillier::decrypt_with_threshold(&corrupted, &c, usize::MAX),
+            m
+        );
+
+        let mut corrupted = dk.clone();
+        corrupted.dp = BigInt::one();
+        corrupted.dq = BigInt::one();
+        assert_eq!(Paillier::decrypt(&corrupted, &c), m);
+        assert_ne!(
+            Paillier::try_open(&corrupted, &c),
+            Paillier::try_open(&dk, &c)
+        );
+    }
+
+    #[test]
+    fn test_valid_randomness() {
+        let keypair = test_keypair();
+        let (ek, _) = keypair.keys();
+
+        assert!(ek.is_valid_randomness(&BigInt::one()));
+        assert!(ek.is_valid_randomness(&Randomness::sample(&ek).0));
+        assert!(!ek.is_valid_randomness(&BigInt::zero()));
+        assert!(!ek.is_valid_randomness(&ek.n));
+        assert!(!ek.is_valid_randomness(&(&ek.n + 1)));
+        assert!(!ek.is_valid_randomness(&keypair.p));
+        assert!(!ek.is_valid_randomness(&(&keypair.q * BigInt::from(2))));
+    }
+
+    #[test]
+    fn test_typed_randomness() {
+        let (ek, dk) = test_keypair().keys();
+        let m = BigInt::from(10);
+
+        let secret = SecretRandomness::sample(&ek);
+        let c = Paillier::encrypt_with_secret_randomness(&ek, RawPlaintext::from(&m), &secret);
+        let (decrypted, opened) = Paillier::open_public(&dk, &c).unwrap();
+        assert_eq!(decrypted, RawPlaintext::from(&m));
+
+        let public = secret.reveal();
+        assert_eq!(opened, public);
+        let c_public =
+            Paillier::encrypt_with_public_randomness(&ek, RawPlaintext::from(&m), &public);
+        assert_eq!(c_public, c);
+    }
+
+    #[test]
+    fn test_encrypt_bits() {
+        let (ek, dk) = test_keypair().keys();
+        let m = BigInt::from(0b1011_0010);
+
+        let bits = Paillier::encrypt_bits(&ek, &m, 8).unwrap();
+        assert_eq!(bits.len(), 8);
+        for (i, c) in bits.iter().enumerate() {
+            let bit: BigInt = Paillier::decrypt(&dk, c).into();
+            assert!(bit == BigInt::zero() || bit == BigInt::one());
+            assert_eq!(bit == BigInt::one(), m.test_bit(i));
+        }
+        assert_eq!(Paillier::decrypt_bits(&dk, &bits), m);
+
+        assert_eq!(
+            Paillier::encrypt_bits(&ek, &BigInt::from(256), 8),
+            Err(Error::PlaintextOutOfRange)
+        );
+        assert_eq!(
+            Paillier::encrypt_bits(&ek, &BigInt::from(-1), 8),
+            Err(Error::PlaintextOutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_verify_opening() {
+        let (ek, dk) = test_keypair().keys();
+        let m = RawPlaintext::from(BigInt::from(10));
+        let c = Paillier::encrypt(&ek, m.clone());
+        let (_, r) = Paillier::open(&dk, &c);
+
+        assert!(Paillier::verify_opening(&ek, &c, &m, &r));
+        let wrong = RawPlaintext::from(BigInt::from(11));
+        assert!(!Paillier::verify_opening(&ek, &c, &wrong, &r));
+        let wrapped = RawPlaintext::from(BigInt::from(10) + &ek.n);
+        assert!(!Paillier::verify_opening(&ek, &c, &wrapped, &r));
+        let other_r = Randomness::sample(&ek);
+        assert!(!Paillier::verify_opening(&ek, &c, &m, &other_r));
+    }
+
     #[test]
     fn test_correct_opening() {
         let (ek, dk) = test_keypair().keys();
 
         let c = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(10)));
         let (m, r) = Paillier::open(&dk, &c);
+        assert_eq!(
+            Paillier::try_open(&dk, &c),
+            Ok((m.clone(), Randomness(r.0.clone())))
+        );
         let d = Paillier::encrypt_with_chosen_randomness(&ek, m, &r);
         assert_eq!(c, d);
     }
 
+    #[test]
+    fn test_try_open_not_coprime() {
+        let keypair = test_keypair();
+        let (_, dk) = keypair.keys();
+
+        let c = RawCiphertext::from(&keypair.p);
+        assert_eq!(Paillier::try_open(&dk, &c), Err(Error::NotCoprime));
+        assert_eq!(Paillier::open_public(&dk, &c), Err(Error::NotCoprime));
+    }
+
+    #[test]
+    fn test_extract_nroot() {
+        let keypair = test_keypair();
+        let (ek, dk) = keypair.keys();
+
+        let r = Randomness::sample(&ek);
+        let rn = BigInt::mod_pow(&r.0, &ek.n, &ek.nn);
+        assert_eq!(extract_nroot(&dk, &rn), Ok(r.0));
+
+        assert_eq!(extract_nroot(&dk, &keypair.p), Err(Error::NotCoprime));
+        assert_eq!(extract_nroot(&dk, &BigInt::zero()), Err(Error::NotCoprime));
+
+        // n sharing a factor with phi is a broken key, rejected when the key is built
+        let malformed = MinimalDecryptionKey {
+            p: BigInt::from(3),
+            q: BigInt::from(7),
+        };
+        assert_eq!(DecryptionKey::try_from(malformed), Err(Error::InvalidKey));
+    }
+
+    #[test]
+    fn test_extract_nroot_blinded() {
+        let keypair = test_keypair();
+        let (ek, dk) = keypair.keys();
+
+        let r = Randomness::sample(&ek);
+        let rn = BigInt::mod_pow(&r.0, &ek.n, &ek.nn);
+        let blinded = extract_nroot_blinded(&dk, &rn);
+        assert_eq!(blinded, extract_nroot_unblinded(&dk, &rn));
+        assert_eq!(blinded, Ok(r.0));
+
+        assert_eq!(
+            extract_nroot_blinded(&dk, &keypair.p),
+            Err(Error::NotCoprime)
+        );
+    }
+
     #[test]
     fn test_correct_addition() {
         let (ek, dk) = test_keypair().keys();
@@ -587,6 +2599,313 @@ mod tests {
         assert_eq!(m, BigInt::from(200).into());
     }
 
+    #[test]
+    fn test_correct_negation() {
+        let (ek, dk) = test_keypair().keys();
+
+        for _ in 0..10 {
+            let m = BigInt::sample_below(&ek.n);
+            let c = Paillier::encrypt(&ek, RawPlaintext::from(&m));
+
+            let expected = BigInt::mod_sub(&BigInt::zero(), &m, &ek.n);
+            let d = Paillier::neg(&ek, RawCiphertext::from(c.0.borrow() as &BigInt));
+            assert_eq!(Paillier::decrypt(&dk, d), RawPlaintext::from(&expected));
+
+            // matches the exponentiation-based negation
+            let e = BigInt::mod_pow(&c.0, &(&ek.n - BigInt::one()), &ek.nn);
+            assert_eq!(
+                Paillier::decrypt(&dk, RawCiphertext::from(e)),
+                RawPlaintext::from(&expected)
+            );
+
+            let sum = Paillier::add(
+                &ek,
+                c,
+                Paillier::neg(&ek, Paillier::encrypt(&ek, RawPlaintext::from(&m))),
+            );
+            assert_eq!(Paillier::decrypt(&dk, sum), BigInt::zero().into());
+        }
+    }
+
+    #[test]
+    fn test_boolean_with_plaintext() {
+        let (ek, dk) = test_keypair().keys();
+
+        for &a in &[false, true] {
+            for &b in &[false, true] {
+                let c = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(a as u32)));
+                let and =
+                    Paillier::and_with_plaintext(&ek, RawCiphertext::from(&c.0 as &BigInt), b);
+                let or = Paillier::or_with_plaintext(&ek, c, b);
+                assert_eq!(
+                    Paillier::decrypt(&dk, and),
+                    BigInt::from((a && b) as u32).into()
+                );
+                assert_eq!(
+                    Paillier::decrypt(&dk, or),
+                    BigInt::from((a || b) as u32).into()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let (ek, dk) = test_keypair().keys();
+
+        let c = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(10)));
+        let unreduced = RawCiphertext::from(&c.0 as &BigInt + &ek.nn * BigInt::from(3));
+        let negative = RawCiphertext::from(&c.0 as &BigInt - &ek.nn);
+        assert_ne!(unreduced, c);
+        assert_eq!(unreduced.canonicalize(&ek), c.canonicalize(&ek));
+        assert_eq!(negative.canonicalize(&ek), c);
+        assert_eq!(c.canonicalize(&ek), c);
+
+        // homomorphic operations produce canonical output from unreduced input
+        let d = Paillier::add(&ek, negative, RawPlaintext::from(BigInt::from(5)));
+        assert_eq!(d.canonicalize(&ek), d);
+        assert_eq!(Paillier::decrypt(&dk, d), BigInt::from(15).into());
+    }
+
+    #[test]
+    fn test_affine() {
+        let (ek, dk) = test_keypair().keys();
+
+        let c = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(5)));
+        let d = Paillier::affine(&ek, RawCiphertext::from(&c.0 as &BigInt), 3, 2);
+        assert_eq!(Paillier::decrypt(&dk, d), BigInt::from(17).into());
+
+        let d = Paillier::affine(&ek, RawCiphertext::from(&c.0 as &BigInt), -3, 2);
+        assert_eq!(Paillier::decrypt(&dk, d), RawPlaintext::from(&ek.n - 13));
+
+        let d = Paillier::affine(&ek, c, 4, -30);
+        assert_eq!(Paillier::decrypt(&dk, d), RawPlaintext::from(&ek.n - 10));
+    }
+
+    #[test]
+    fn test_ciphertext_from_str() {
+        let (ek, dk) = test_keypair().keys();
+
+        let c = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(10)));
+        let s = c.0.to_str_radix(10);
+        let parsed = RawCiphertext::try_from(s.as_str()).unwrap();
+        assert_eq!(parsed.validate(&ek), Ok(()));
+        assert_eq!(Paillier::decrypt(&dk, parsed), BigInt::from(10).into());
+
+        assert_eq!(RawCiphertext::try_from("-5"), Err(Error::InvalidEncoding));
+        assert_eq!(RawCiphertext::try_from("12ab"), Err(Error::InvalidEncoding));
+
+        let overflowing = ek.nn.to_str_radix(10);
+        let parsed = RawCiphertext::try_from(overflowing.as_str()).unwrap();
+        assert_eq!(parsed.validate(&ek), Err(Error::CiphertextOutOfRange));
+    }
+
+    #[test]
+    fn test_verify_ciphertext_ct() {
+        let (ek, _) = test_keypair().keys();
+
+        let m = RawPlaintext::from(BigInt::from(10));
+        let r = Randomness::sample(&ek);
+        let c = Paillier::encrypt_with_chosen_randomness(&ek, m.clone(), &r);
+        assert!(bool::from(ek.verify_ciphertext_ct(&c, &m, &r)));
+
+        let other_m = RawPlaintext::from(BigInt::from(11));
+        assert!(!bool::from(ek.verify_ciphertext_ct(&c, &other_m, &r)));
+
+        let other_r = Randomness::sample(&ek);
+        assert!(!bool::from(ek.verify_ciphertext_ct(&c, &m, &other_r)));
+
+        let unreduced = RawCiphertext::from(&c.0 as &BigInt + &ek.nn);
+        assert!(!bool::from(ek.verify_ciphertext_ct(&unreduced, &m, &r)));
+    }
+
+    #[test]
+    fn test_display_from_str() {
+        let (ek, dk) = test_keypair().keys();
+
+        let ek_recovered: EncryptionKey = ek.to_string().parse().unwrap();
+        assert_eq!(ek_recovered, ek);
+        assert_eq!(ek.to_string(), ek.n.to_str_radix(10));
+        assert_eq!("1".parse::<EncryptionKey>(), Err(Error::InvalidEncoding));
+
+        let c = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(10)));
+        let c_recovered: RawCiphertext = c.to_string().parse().unwrap();
+        assert_eq!(c_recovered, c);
+
+        let m = RawPlaintext::from(BigInt::from(-10));
+        let m_recovered: RawPlaintext = m.to_string().parse().unwrap();
+        assert_eq!(m_recovered, m);
+        assert_eq!(m.to_string(), "-10");
+
+        let dk_displayed = dk.to_string();
+        assert!(!dk_displayed.contains(&dk.p.to_str_radix(10)));
+        assert!(!dk_displayed.contains(&dk.q.to_str_radix(10)));
+    }
+
+    #[test]
+    fn test_parsing_is_canonical() {
+        let (ek, _) = test_keypair().keys();
+        let value = BigInt::mod_sub(&ek.nn, &BigInt::from(12345), &ek.nn);
+        let digits = value.to_str_radix(10);
+
+        let from_str = BigInt::from_str_radix(&format!("000{}", digits), 10).unwrap();
+        let mut bytes = vec![0, 0];
+        bytes.extend(value.to_bytes());
+        let from_bytes = BigInt::from_bytes(&bytes);
+        assert_eq!(from_str, from_bytes);
+        assert_eq!(from_str.to_bytes(), from_bytes.to_bytes());
+
+        let c_str: RawCiphertext = format!("00{}", digits).parse().unwrap();
+        let c_json: RawCiphertext = serde_json::from_str(&format!("\"{}\"", digits)).unwrap();
+        let mut tagged = ek.fingerprint().to_be_bytes().to_vec();
+        tagged.extend(&bytes);
+        let c_bytes = RawCiphertext::from_tagged_bytes(&ek, &tagged).unwrap();
+        assert_eq!(c_str, c_bytes);
+        assert_eq!(c_json, c_bytes);
+
+        let zero: RawPlaintext = "-0".parse().unwrap();
+        assert_eq!(zero, RawPlaintext::from(BigInt::zero()));
+    }
+
+    #[test]
+    fn test_tagged_bytes() {
+        let (ek, dk) = test_keypair().keys();
+        let (other_ek, _) = Paillier::keypair_unsafe_small(128).keys();
+
+        let c = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(10)));
+        let bytes = c.to_tagged_bytes(&ek);
+
+        let recovered = RawCiphertext::from_tagged_bytes(&ek, &bytes).unwrap();
+        assert_eq!(recovered, c);
+        assert_eq!(Paillier::decrypt(&dk, recovered), BigInt::from(10).into());
+
+        assert_eq!(
+            RawCiphertext::from_tagged_bytes(&other_ek, &bytes),
+            Err(Error::ModulusMismatch)
+        );
+        assert_eq!(
+            RawCiphertext::from_tagged_bytes(&ek, &bytes[..4]),
+            Err(Error::InvalidEncoding)
+        );
+    }
+
+    #[test]
+    fn test_operator_addition() {
+        let (ek, dk) = test_keypair().keys();
+
+        let a = WithKey::new(
+            &ek,
+            Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(10))),
+        );
+        let b = WithKey::new(
+            &ek,
+            Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(20))),
+        );
+
+        for c in [
+            &a + &b,
+            a.clone() + &b,
+            &a + b.clone(),
+            a.clone() + b.clone(),
+        ] {
+            let m = Paillier::decrypt(&dk, c.value);
+            assert_eq!(m, BigInt::from(30).into());
+        }
+    }
+
+    #[test]
+    fn test_operator_multiplication() {
+        let (ek, dk) = test_keypair().keys();
+
+        let a = WithKey::new(
+            &ek,
+            Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(10))),
+        );
+        let m = RawPlaintext::from(BigInt::from(20));
+
+        for c in [
+            &a * &m,
+            a.clone() * &m,
+            &a * m.clone(),
+            a.clone() * m.clone(),
+        ] {
+            let m = Paillier::decrypt(&dk, c.value);
+            assert_eq!(m, BigInt::from(200).into());
+        }
+    }
+
+    #[test]
+    fn test_operator_chaining() {
+        let (ek, dk) = test_keypair().keys();
+
+        let a = WithKey::new(
+            &ek,
+            Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(10))),
+        );
+        let b = WithKey::new(
+            &ek,
+            Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(20))),
+        );
+
+        let c = &(&a + &b) * &RawPlaintext::from(BigInt::from(2)) + &a;
+        let m = Paillier::decrypt(&dk, c.value);
+        assert_eq!(m, BigInt::from(70).into());
+    }
+
+    #[test]
+    fn test_modulus_mismatch() {
+        let (ek, dk) = test_keypair().keys();
+        let (other_ek, _) = Paillier::keypair_unsafe_small(128).keys();
+        assert_ne!(ek.fingerprint(), other_ek.fingerprint());
+        assert_eq!(ek.fingerprint(), dk.encryption_key().fingerprint());
+        assert_eq!(ek.fingerprint(), dk.clone().into_public().fingerprint());
+        assert_eq!(ek.fingerprint(), EncryptionKey::from(&ek.n).fingerprint());
+
+        let a = WithKey::new(
+            &ek,
+            Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(10))),
+        );
+        let b = WithKey::new(
+            &other_ek,
+            Paillier::encrypt(&other_ek, RawPlaintext::from(BigInt::from(20))),
+        );
+        assert_eq!(a.checked_add(&b), Err(Error::ModulusMismatch));
+        assert_eq!(b.checked_add(&a), Err(Error::ModulusMismatch));
+
+        // swapping the key of a tagged ciphertext is caught as well
+        let mut swapped = b.clone();
+        swapped.ek = &ek;
+        assert_eq!(a.checked_add(&swapped), Err(Error::ModulusMismatch));
+        let two = RawPlaintext::from(BigInt::from(2));
+        assert_eq!(swapped.checked_mul(&two), Err(Error::ModulusMismatch));
+
+        let c = a.checked_add(&a).unwrap();
+        assert_eq!(c.fingerprint(), ek.fingerprint());
+        let m = Paillier::decrypt(&dk, c.value);
+        assert_eq!(m, BigInt::from(20).into());
+
+        let c = a.checked_mul(&two).unwrap();
+        let m = Paillier::decrypt(&dk, c.value);
+        assert_eq!(m, BigInt::from(20).into());
+    }
+
+    #[test]
+    #[should_panic(expected = "operands are under different moduli")]
+    fn test_operator_modulus_mismatch() {
+        let (ek, _) = test_keypair().keys();
+        let (other_ek, _) = Paillier::keypair_unsafe_small(128).keys();
+
+        let a = WithKey::new(
+            &ek,
+            Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(10))),
+        );
+        let b = WithKey::new(
+            &other_ek,
+            Paillier::encrypt(&other_ek, RawPlaintext::from(BigInt::from(20))),
+        );
+        let _ = &a + &b;
+    }
+
     #[test]
     fn test_correct_keygen() {
         let (ek, dk): (EncryptionKey, _) = Paillier::keypair_with_modulus_size(2048).keys();
@@ -611,11 +2930,88 @@ mod tests {
         assert_eq!(dk, dk_recovered);
     }
 
+    #[test]
+    fn test_key_hash() {
+        use std::collections::HashSet;
+
+        let (ek, _) = test_keypair().keys();
+        let (other_ek, _) = Paillier::keypair_unsafe_small(128).keys();
+
+        let mut keys = HashSet::new();
+        keys.insert(ek.clone());
+        keys.insert(test_keypair().public_key());
+        assert_eq!(keys.len(), 1);
+        keys.insert(other_ek);
+        assert_eq!(keys.len(), 2);
+        assert!(keys.contains(&ek));
+
+        let mut minimal_keys = HashSet::new();
+        minimal_keys.insert(MinimalEncryptionKey::from(&ek));
+        minimal_keys.insert(MinimalEncryptionKey::from(&test_keypair()));
+        assert_eq!(minimal_keys.len(), 1);
+    }
+
+    #[test]
+    fn test_ciphertext_serialization() {
+        let (ek, dk) = test_keypair().keys();
+
+        let c = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(10)));
+        let c_serialized = serde_json::to_string(&c).unwrap();
+        let c_recovered: RawCiphertext = serde_json::from_str(&c_serialized).unwrap();
+        assert_eq!(c_recovered, c);
+        assert_eq!(c_recovered.validate(&ek), Ok(()));
+        assert_eq!(Paillier::decrypt(&dk, c_recovered), BigInt::from(10).into());
+
+        let invalid = format!("\"{}\"", ek.nn.to_str_radix(10));
+        let c_invalid: RawCiphertext = serde_json::from_str(&invalid).unwrap();
+        assert_eq!(c_invalid.validate(&ek), Err(Error::CiphertextOutOfRange));
+
+        let negative: RawCiphertext = serde_json::from_str("\"-1\"").unwrap();
+        assert_eq!(negative.validate(&ek), Err(Error::CiphertextOutOfRange));
+    }
+
     #[test]
     fn test_failing_deserialize() {
         let illformatted = "{\"n\":\"12345abcdef\"}";
 
-        let result: Result<EncryptionKey, _> = serde_json::from_str(&illformatted);
+        let result: Result<EncryptionKey, _> = serde_json::from_str(illformatted);
         assert!(result.is_err())
     }
+
+    #[test]
+    fn test_deserialize_degenerate_modulus() {
+        for invalid in &["{\"n\":\"0\"}", "{\"n\":\"1\"}", "{\"n\":\"-7\"}"] {
+            let result: Result<EncryptionKey, _> = serde_json::from_str(invalid);
+            assert!(result.is_err(), "{}", invalid);
+            let result: Result<MinimalEncryptionKey, _> = serde_json::from_str(invalid);
+            assert!(result.is_err(), "{}", invalid);
+        }
+
+        let ek: EncryptionKey = serde_json::from_str("{\"n\":\"15\"}").unwrap();
+        assert_eq!(ek.n, BigInt::from(15));
+    }
+
+    #[test]
+    fn test_deserialize_invalid_decryption_key() {
+        for invalid in &[
+            "{\"p\":\"1\",\"q\":\"1\"}",
+            "{\"p\":\"0\",\"q\":\"7\"}",
+            "{\"p\":\"-5\",\"q\":\"7\"}",
+            "{\"p\":\"7\",\"q\":\"7\"}",
+            "{\"p\":\"6\",\"q\":\"9\"}",
+        ] {
+            let result: Result<DecryptionKey, _> = serde_json::from_str(invalid);
+            assert!(result.is_err(), "{}", invalid);
+        }
+
+        let minimal = MinimalDecryptionKey {
+            p: BigInt::from(7),
+            q: BigInt::from(7),
+        };
+        assert_eq!(DecryptionKey::try_from(minimal), Err(Error::InvalidKey));
+
+        let dk = DecryptionKey::from(&test_keypair());
+        let recovered = DecryptionKey::try_from(MinimalDecryptionKey::from(&dk));
+        assert_eq!(recovered, Ok(dk));
+    }
 }
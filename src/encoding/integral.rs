@@ -89,6 +89,20 @@ where
     }
 }
 
+impl Paillier {
+    /// Decrypt to the underlying integer, leaving any packed components in place.
+    ///
+    /// Component `i` of a ciphertext packing `k` components of 64 bits each occupies
+    /// bits `64 * (k - 1 - i)` up to `64 * (k - i)`, allowing custom bit-field extraction.
+    pub fn decrypt_raw_bigint<DK, T>(dk: &DK, c: &EncodedCiphertext<T>) -> BigInt
+    where
+        for<'c, 'p> Self: Decrypt<DK, RawCiphertext<'c>, RawPlaintext<'p>>,
+    {
+        let m = Self::decrypt(dk, RawCiphertext::from(&c.raw));
+        m.into()
+    }
+}
+
 impl<EK, C1, C2> Add<EK, C1, C2, EncodedCiphertext<u64>> for Paillier
 where
     for<'c1, 'c2, 'd> Self: Add<EK, RawCiphertext<'c1>, RawCiphertext<'c2>, RawCiphertext<'d>>,
@@ -339,6 +353,34 @@ mod tests {
         assert_eq!(recovered_m, m);
     }
 
+    #[test]
+    fn test_raw_ciphertext_conversion() {
+        let (ek, dk) = test_keypair().keys();
+
+        let c: EncodedCiphertext<u64> = Paillier::encrypt(&ek, 10);
+        let raw = RawCiphertext::from(c);
+        let m: RawPlaintext = Paillier::decrypt(&dk, &raw);
+        assert_eq!(crate::decode(&m), Ok(10));
+
+        let c = EncodedCiphertext::<u64>::from(Paillier::encrypt(&ek, crate::encode(20)));
+        let m: u64 = Paillier::decrypt(&dk, &c);
+        assert_eq!(m, 20);
+    }
+
+    #[test]
+    fn test_scalar_ciphertext_from_str() {
+        let (ek, dk) = test_keypair().keys();
+
+        let c: EncodedCiphertext<u64> = Paillier::encrypt(&ek, 10);
+        let s = BigInt::from(RawCiphertext::from(c)).to_str_radix(10);
+        let parsed = EncodedCiphertext::<u64>::try_from(s.as_str()).unwrap();
+        assert_eq!(parsed.validate(&ek), Ok(()));
+        assert_eq!(Paillier::decrypt(&dk, &parsed), 10);
+
+        let result = EncodedCiphertext::<u64>::try_from("-1");
+        assert_eq!(result, Err(crate::Error::InvalidEncoding));
+    }
+
     #[test]
     fn test_vector_encrypt_decrypt() {
         let (ek, dk) = test_keypair().keys();
@@ -416,6 +458,26 @@ mod tests {
     //     assert_eq!(m, vec![2, 4, 6]);
     // }
 
+    #[test]
+    fn test_decrypt_raw_bigint() {
+        let (ek, dk) = test_keypair().keys();
+
+        let m = vec![1, 2, u64::MAX];
+        let c = Paillier::encrypt(&ek, &*m);
+        let raw = Paillier::decrypt_raw_bigint(&dk, &c);
+
+        let mask = (BigInt::one() << 64) - BigInt::one();
+        let sliced: Vec<u64> = (0..m.len())
+            .map(|i| {
+                let slot = (&raw >> (64 * (m.len() - 1 - i))) & &mask;
+                u64::try_from(&slot).unwrap()
+            })
+            .collect();
+        let unpacked: Vec<u64> = Paillier::decrypt(&dk, &c);
+        assert_eq!(sliced, unpacked);
+        assert_eq!(sliced, m);
+    }
+
     #[test]
     fn test_scalar_mul_plaintext_scalar() {
         let (ek, dk) = test_keypair().keys();
@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 
 pub mod integral;
 
-use crate::BigInt;
+use crate::{BigInt, EncryptionKey, Error, RawCiphertext, RawPlaintext};
 
 /// Encrypted message with type information.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -19,6 +19,67 @@ pub struct EncodedCiphertext<T> {
     _phantom: PhantomData<T>,
 }
 
+/// Encode a scalar as a raw plaintext.
+pub fn encode(m: u64) -> RawPlaintext<'static> {
+    RawPlaintext::from(BigInt::from(m))
+}
+
+/// Decode a raw plaintext as a scalar, failing if it does not fit into a `u64`.
+pub fn decode(m: &RawPlaintext) -> Result<u64, Error> {
+    u64::try_from(m.0.as_ref()).map_err(|_| Error::DecodingOverflow)
+}
+
+impl From<u64> for RawPlaintext<'static> {
+    fn from(m: u64) -> Self {
+        encode(m)
+    }
+}
+
+impl<'b> TryFrom<RawPlaintext<'b>> for u64 {
+    type Error = Error;
+
+    fn try_from(m: RawPlaintext<'b>) -> Result<u64, Error> {
+        decode(&m)
+    }
+}
+
+impl<T> From<EncodedCiphertext<T>> for RawCiphertext<'static> {
+    fn from(c: EncodedCiphertext<T>) -> Self {
+        RawCiphertext::from(c.raw)
+    }
+}
+
+impl<'b> From<RawCiphertext<'b>> for EncodedCiphertext<u64> {
+    /// Attach scalar type information to a raw ciphertext.
+    ///
+    /// The range of the underlying plaintext can only be checked on decryption.
+    fn from(c: RawCiphertext<'b>) -> Self {
+        EncodedCiphertext {
+            raw: c.into(),
+            components: 1,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// Parse a scalar ciphertext from its decimal representation.
+///
+/// As for `RawCiphertext`, the key-dependent upper bound is checked by `validate`.
+impl<'s> TryFrom<&'s str> for EncodedCiphertext<u64> {
+    type Error = Error;
+
+    fn try_from(s: &'s str) -> Result<Self, Error> {
+        Ok(EncodedCiphertext::from(RawCiphertext::try_from(s)?))
+    }
+}
+
+impl<T> EncodedCiphertext<T> {
+    /// Check that the underlying ciphertext lies in `[0, n^2)`.
+    pub fn validate(&self, ek: &EncryptionKey) -> Result<(), Error> {
+        RawCiphertext::from(&self.raw).validate(ek)
+    }
+}
+
 fn pack<T>(components: &[T], component_bitsize: usize) -> BigInt
 where
     BigInt: From<T>,
@@ -61,7 +122,7 @@ fn test_pack() {
 
     let component_bitsize = 64;
 
-    let packed = pack(&*v, component_bitsize);
+    let packed = pack(&v, component_bitsize);
     assert_eq!(
         packed,
         BigInt::from(1) * (BigInt::from(1) << (2 * component_bitsize))
@@ -72,3 +133,17 @@ fn test_pack() {
     let unpacked: Vec<u64> = unpack(packed, component_bitsize, 3);
     assert_eq!(unpacked, v);
 }
+
+#[test]
+fn test_encode_decode() {
+    assert_eq!(decode(&encode(10)), Ok(10));
+    assert_eq!(decode(&encode(u64::MAX)), Ok(u64::MAX));
+    assert_eq!(u64::try_from(RawPlaintext::from(42)), Ok(42));
+
+    let overflow = RawPlaintext::from(BigInt::from(u64::MAX) + BigInt::from(1));
+    assert_eq!(decode(&overflow), Err(Error::DecodingOverflow));
+    assert_eq!(u64::try_from(overflow), Err(Error::DecodingOverflow));
+
+    let negative = RawPlaintext::from(BigInt::from(-1));
+    assert_eq!(decode(&negative), Err(Error::DecodingOverflow));
+}
@@ -0,0 +1,53 @@
+//! Errors returned by the fallible operations of the library.
+
+use std::fmt;
+
+/// Reasons an operation may be rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Plaintext equals the modulus `n` and would encrypt the same as zero.
+    PlaintextEqualsModulus,
+    /// Plaintext is negative or not below the modulus `n`.
+    PlaintextOutOfRange,
+    /// Plaintext is zero while zero was explicitly disallowed.
+    ZeroPlaintext,
+    /// Plaintext does not fit into the type it is decoded as.
+    DecodingOverflow,
+    /// Value shares a factor with the modulus `n`, so the required inverse does not exist.
+    NotCoprime,
+    /// Input is not a non-negative decimal integer.
+    InvalidEncoding,
+    /// Ciphertext is negative or not below `n^2`.
+    CiphertextOutOfRange,
+    /// Plaintext could grow beyond the modulus `n` and silently wrap around.
+    BoundExceeded,
+    /// Operands were encrypted under different moduli.
+    ModulusMismatch,
+    /// Lengths of vectors or matrix rows do not agree.
+    DimensionMismatch,
+    /// Primes of a decryption key are not distinct, greater than one and coprime.
+    InvalidKey,
+    /// Parameter of a distribution is outside its valid range.
+    InvalidParameter,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::PlaintextEqualsModulus => write!(f, "plaintext equals the modulus"),
+            Error::PlaintextOutOfRange => write!(f, "plaintext is outside [0, n)"),
+            Error::ZeroPlaintext => write!(f, "plaintext is zero"),
+            Error::DecodingOverflow => write!(f, "plaintext does not fit the decoded type"),
+            Error::NotCoprime => write!(f, "value is not coprime to the modulus"),
+            Error::InvalidEncoding => write!(f, "not a non-negative decimal integer"),
+            Error::CiphertextOutOfRange => write!(f, "ciphertext is outside [0, n^2)"),
+            Error::BoundExceeded => write!(f, "plaintext could exceed the modulus"),
+            Error::ModulusMismatch => write!(f, "operands are under different moduli"),
+            Error::DimensionMismatch => write!(f, "operand dimensions do not agree"),
+            Error::InvalidKey => write!(f, "decryption key primes are invalid"),
+            Error::InvalidParameter => write!(f, "distribution parameter is out of range"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -1,21 +1,224 @@
 //! Key generation following standard recommendations.
 
 use curv::arithmetic::traits::*;
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
 
 use crate::traits::*;
 use crate::{BigInt, Keypair, Paillier};
 
 impl KeyGeneration<Keypair> for Paillier {
     fn keypair_with_modulus_size(bit_length: usize) -> Keypair {
-        let p = BigInt::sample_prime(bit_length / 2);
-        let q = BigInt::sample_prime(bit_length / 2);
-        Keypair { p, q }
+        KeygenBuilder::new().bits(bit_length).generate()
     }
 
     fn keypair_safe_primes_with_modulus_size(bit_length: usize) -> Keypair {
-        let p = BigInt::sample_safe_prime(bit_length / 2);
-        let q = BigInt::sample_safe_prime(bit_length / 2);
-        Keypair { p, q }
+        KeygenBuilder::new()
+            .bits(bit_length)
+            .safe_primes()
+            .generate()
+    }
+}
+
+impl Paillier {
+    /// Generate fresh key pair where both primes are congruent to 3 modulo 4,
+    /// making the modulus a Blum integer.
+    pub fn keypair_blum(bit_length: usize) -> Keypair {
+        KeygenBuilder::new().bits(bit_length).blum().generate()
+    }
+
+    /// Generate fresh key pair drawing all prime candidates from `rng`.
+    ///
+    /// The primality tests themselves still use fresh system randomness, which does not
+    /// affect the result, so a seeded `rng` gives a reproducible key pair.
+    pub fn keypair_with_rng<R: RngCore + CryptoRng>(bit_length: usize, rng: &mut R) -> Keypair {
+        KeygenBuilder::new().bits(bit_length).rng(rng).generate()
+    }
+}
+
+/// Options for generating a key pair.
+///
+/// Defaults to a 2048 bit modulus of random primes drawn from system randomness and
+/// tested with five Miller-Rabin rounds.
+///
+/// ```
+/// use kzen_paillier::*;
+///
+/// let keypair = KeygenBuilder::new().bits(512).blum().mr_rounds(10).generate();
+/// ```
+pub struct KeygenBuilder<'r> {
+    bit_length: usize,
+    safe_primes: bool,
+    blum: bool,
+    rng: Option<(&'r mut dyn RngCore, &'static str)>,
+    mr_rounds: usize,
+    modulus_predicate: Option<ModulusPredicate<'r>>,
+}
+
+type ModulusPredicate<'r> = Box<dyn Fn(&BigInt) -> bool + 'r>;
+
+/// Source of the randomness a key pair was generated from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RngSource {
+    /// System randomness through `OsRng`.
+    OsRng,
+    /// Generator passed by the caller, identified by its type name.
+    UserSupplied(&'static str),
+}
+
+/// Non-secret description of how a key pair was generated, e.g. for compliance logs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyProvenance {
+    pub rng: RngSource,
+    pub bit_length: usize,
+    pub mr_rounds: usize,
+}
+
+impl<'r> KeygenBuilder<'r> {
+    pub fn new() -> Self {
+        KeygenBuilder {
+            bit_length: 2048,
+            safe_primes: false,
+            blum: false,
+            rng: None,
+            mr_rounds: 5,
+            modulus_predicate: None,
+        }
+    }
+
+    /// Size of the modulus in bits.
+    pub fn bits(mut self, bit_length: usize) -> Self {
+        self.bit_length = bit_length;
+        self
+    }
+
+    /// Use safe primes `p = 2p' + 1` for prime `p'`, which are also Blum primes.
+    pub fn safe_primes(mut self) -> Self {
+        self.safe_primes = true;
+        self
+    }
+
+    /// Use primes congruent to 3 modulo 4, making the modulus a Blum integer.
+    pub fn blum(mut self) -> Self {
+        self.blum = true;
+        self
+    }
+
+    /// Draw all prime candidates from `rng` instead of system randomness.
+    pub fn rng<R: RngCore + CryptoRng>(mut self, rng: &'r mut R) -> Self {
+        self.rng = Some((rng, std::any::type_name::<R>()));
+        self
+    }
+
+    /// Number of Miller-Rabin rounds run on each candidate passing trial division.
+    pub fn mr_rounds(mut self, mr_rounds: usize) -> Self {
+        self.mr_rounds = mr_rounds;
+        self
+    }
+
+    /// Only accept key pairs whose modulus `n` satisfies `predicate`.
+    ///
+    /// Both primes are re-sampled until it holds, so generation takes on average `1 / P`
+    /// times as long for a predicate holding with probability `P`; a predicate that is
+    /// never satisfied makes generation loop forever.
+    pub fn with_modulus_predicate<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&BigInt) -> bool + 'r,
+    {
+        self.modulus_predicate = Some(Box::new(predicate));
+        self
+    }
+
+    pub fn generate(self) -> Keypair {
+        self.generate_with_provenance().0
+    }
+
+    /// Generate key pair together with a description of how it was generated.
+    pub fn generate_with_provenance(self) -> (Keypair, KeyProvenance) {
+        let mut os_rng = os_rng();
+        let (rng, source): (&mut dyn RngCore, _) = match self.rng {
+            Some((rng, name)) => (rng, RngSource::UserSupplied(name)),
+            None => (&mut os_rng, RngSource::OsRng),
+        };
+        let bitsize = self.bit_length / 2;
+        let rounds = self.mr_rounds;
+        let keypair = loop {
+            let keypair = if self.safe_primes {
+                keypair_from_prime_sampler(|| sample_safe_prime_with_rng(bitsize, rng, rounds))
+            } else if self.blum {
+                keypair_from_prime_sampler(|| sample_blum_prime_with_rng(bitsize, rng, rounds))
+            } else {
+                keypair_from_prime_sampler(|| sample_prime_with_rng(bitsize, rng, rounds))
+            };
+            match &self.modulus_predicate {
+                Some(predicate) if !predicate(&(&keypair.p * &keypair.q)) => continue,
+                _ => break keypair,
+            }
+        };
+        let provenance = KeyProvenance {
+            rng: source,
+            bit_length: self.bit_length,
+            mr_rounds: rounds,
+        };
+        (keypair, provenance)
+    }
+}
+
+impl<'r> Default for KeygenBuilder<'r> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn os_rng() -> OsRng {
+    OsRng::new().expect("system randomness is unavailable")
+}
+
+/// Draw primes until they form a usable keypair.
+///
+/// Primes are re-sampled if `p == q` or if `gcd(n, (p-1)(q-1)) != 1`, in which case
+/// the inverses needed for decryption and opening would not exist.
+fn keypair_from_prime_sampler<F>(mut sample_prime: F) -> Keypair
+where
+    F: FnMut() -> BigInt,
+{
+    loop {
+        let p = sample_prime();
+        let q = sample_prime();
+        if p == q {
+            continue;
+        }
+        let n = &p * &q;
+        let phi = (&p - BigInt::one()) * (&q - BigInt::one());
+        if BigInt::gcd(&n, &phi) != BigInt::one() {
+            continue;
+        }
+        return Keypair { p, q };
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl Paillier {
+    /// Generate tiny key pair for fast correctness tests.
+    ///
+    /// **Insecure**: the modulus can be factored instantly for any `bit_length` this is
+    /// meant for. Must never be used outside of tests. Requires `bit_length >= 32`, below
+    /// which the primes would collide with the trial division table.
+    pub fn keypair_unsafe_small(bit_length: usize) -> Keypair {
+        assert!(bit_length >= 32, "modulus size must be at least 32 bits");
+        keypair_from_prime_sampler(|| BigInt::sample_prime(bit_length / 2))
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Paillier {
+    /// Generate fresh key pair on the blocking thread pool of the current tokio runtime.
+    ///
+    /// Key generation takes seconds and would otherwise stall the executor it is called from.
+    pub async fn keypair_blocking_task(bit_length: usize) -> Keypair {
+        tokio::task::spawn_blocking(move || Paillier::keypair_with_modulus_size(bit_length))
+            .await
+            .expect("key generation task panicked")
     }
 }
 
@@ -26,45 +229,94 @@ pub trait PrimeSampable {
 
 impl PrimeSampable for BigInt {
     fn sample_prime(bitsize: usize) -> Self {
-        // See Practical Considerations section inside the section 11.5 "Prime Number Generation"
-        // Applied Cryptography, Bruce Schneier.
-        let one = BigInt::one();
-        let two = &one + &one;
-
-        loop {
-            let mut candidate = Self::sample(bitsize);
-            // We flip the LSB to make sure tue candidate is odd.
-            //  BitManipulation::set_bit(&mut candidate, 0, true);
-            BigInt::set_bit(&mut candidate, 0, true);
-
-            // To ensure the appropiate size
-            // we set the MSB of the candidate.
-            BitManipulation::set_bit(&mut candidate, bitsize - 1, true);
-            // If no prime number is found in 500 iterations,
-            // restart the loop (re-seed).
-            // FIXME: Why 500?
-            for _ in 0..500 {
-                if is_prime(&candidate) {
-                    return candidate;
-                }
-                candidate += &two;
+        sample_prime_with_rng(bitsize, &mut os_rng(), 5)
+    }
+
+    fn sample_safe_prime(bitsize: usize) -> Self {
+        sample_safe_prime_with_rng(bitsize, &mut os_rng(), 5)
+    }
+}
+
+fn sample_prime_with_rng<R: RngCore + ?Sized>(
+    bitsize: usize,
+    rng: &mut R,
+    mr_rounds: usize,
+) -> BigInt {
+    // See Practical Considerations section inside the section 11.5 "Prime Number Generation"
+    // Applied Cryptography, Bruce Schneier.
+    let one = BigInt::one();
+    let two = &one + &one;
+
+    loop {
+        let mut candidate = sample_with_rng(bitsize, rng);
+        // We flip the LSB to make sure tue candidate is odd.
+        //  BitManipulation::set_bit(&mut candidate, 0, true);
+        BigInt::set_bit(&mut candidate, 0, true);
+
+        // To ensure the appropiate size
+        // we set the MSB of the candidate.
+        BitManipulation::set_bit(&mut candidate, bitsize - 1, true);
+        // If no prime number is found in 500 iterations,
+        // restart the loop (re-seed).
+        // FIXME: Why 500?
+        for _ in 0..500 {
+            if is_prime_with_rounds(&candidate, mr_rounds) {
+                return candidate;
             }
+            candidate += &two;
         }
     }
+}
 
-    fn sample_safe_prime(bitsize: usize) -> Self {
-        // q = 2p + 1;
-        let two = BigInt::from(2);
-        loop {
-            let q = PrimeSampable::sample_prime(bitsize);
-            let p = (&q - BigInt::one()).div_floor(&two);
-            if is_prime(&p) {
-                return q;
-            };
+fn sample_safe_prime_with_rng<R: RngCore + ?Sized>(
+    bitsize: usize,
+    rng: &mut R,
+    mr_rounds: usize,
+) -> BigInt {
+    // q = 2p + 1;
+    let two = BigInt::from(2);
+    loop {
+        let q = sample_prime_with_rng(bitsize, rng, mr_rounds);
+        let p = (&q - BigInt::one()).div_floor(&two);
+        if is_prime_with_rounds(&p, mr_rounds) {
+            return q;
+        };
+    }
+}
+
+fn sample_blum_prime_with_rng<R: RngCore + ?Sized>(
+    bitsize: usize,
+    rng: &mut R,
+    mr_rounds: usize,
+) -> BigInt {
+    // p = 3 mod 4
+    let four = BigInt::from(4);
+    let three = BigInt::from(3);
+    loop {
+        let p = sample_prime_with_rng(bitsize, rng, mr_rounds);
+        if p.modulus(&four) == three {
+            return p;
         }
     }
 }
 
+/// Sample integer uniformly from `[0, n)` using `rng`.
+pub(crate) fn sample_below_with_rng<R: RngCore + ?Sized>(n: &BigInt, rng: &mut R) -> BigInt {
+    loop {
+        let x = sample_with_rng(n.bit_length(), rng);
+        if &x < n {
+            return x;
+        }
+    }
+}
+
+/// Sample integer below `2^bitsize` from the bytes of `rng`.
+fn sample_with_rng<R: RngCore + ?Sized>(bitsize: usize, rng: &mut R) -> BigInt {
+    let mut bytes = vec![0u8; bitsize.div_ceil(8)];
+    rng.fill_bytes(&mut bytes);
+    BigInt::from_bytes(&bytes) >> (bytes.len() * 8 - bitsize)
+}
+
 // Runs the following three tests on a given `candidate` to determine
 // primality:
 //
@@ -72,6 +324,11 @@ impl PrimeSampable for BigInt {
 // 2. Run Fermat's Little Theorem against the candidate.
 // 3. Run five rounds of the Miller-Rabin test on the candidate.
 pub fn is_prime(candidate: &BigInt) -> bool {
+    is_prime_with_rounds(candidate, 5)
+}
+
+/// Primality test as `is_prime` but running `mr_rounds` rounds of Miller-Rabin.
+pub fn is_prime_with_rounds(candidate: &BigInt, mr_rounds: usize) -> bool {
     // First, simple trial divide
     for p in SMALL_PRIMES.iter() {
         let prime = BigInt::from(*p);
@@ -89,12 +346,22 @@ pub fn is_prime(candidate: &BigInt) -> bool {
 
     // Finally, do a Miller-Rabin test
     // NIST recommendation is 5 rounds for 512 and 1024 bits. For 1536 bits, the recommendation is 4 rounds.
-    if !miller_rabin(candidate, 5) {
+    if !miller_rabin(candidate, mr_rounds) {
         return false;
     }
     true
 }
 
+/// Check that modulus `n` has no factor among the first 2048 primes.
+///
+/// This is a cheap screening of externally supplied moduli and does not
+/// prove that `n` is a product of two large primes.
+pub fn small_factor_free(n: &BigInt) -> bool {
+    SMALL_PRIMES
+        .iter()
+        .all(|p| !NumberTests::is_zero(&(n % &BigInt::from(*p))))
+}
+
 /// Perform test based on Fermat's little theorem
 /// This might be performed more than once, see Handbook of Applied Cryptography [Algorithm 4.9 p136]
 fn fermat(candidate: &BigInt) -> bool {
@@ -346,3 +613,136 @@ static SMALL_PRIMES: [u32; 2048] = [
     17609, 17623, 17627, 17657, 17659, 17669, 17681, 17683, 17707, 17713, 17729,
     17737, 17747, 17749, 17761, 17783, 17789, 17791, 17807, 17827, 17837, 17839,
     17851, 17863 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RawPlaintext;
+
+    #[test]
+    fn test_small_factor_free() {
+        let (ek, _) = Paillier::keypair_unsafe_small(64).keys();
+        assert!(small_factor_free(&ek.n));
+
+        let p = BigInt::sample_prime(32);
+        assert!(!small_factor_free(&(&p * BigInt::from(17863))));
+        assert!(!small_factor_free(&(&p * BigInt::from(3))));
+    }
+
+    #[test]
+    fn test_keygen_resamples_degenerate_primes() {
+        // mocked sampler first returning equal primes, and then 3 and 7 with 3 | 7 - 1
+        let mut samples = vec![11, 11, 3, 7, 13, 17].into_iter().map(BigInt::from);
+        let keypair = keypair_from_prime_sampler(|| samples.next().unwrap());
+        assert_eq!(keypair.p, BigInt::from(13));
+        assert_eq!(keypair.q, BigInt::from(17));
+    }
+
+    #[test]
+    fn test_keypair_unsafe_small() {
+        let (ek, dk) = Paillier::keypair_unsafe_small(64).keys();
+        assert!(ek.n.bit_length() >= 63 && ek.n.bit_length() <= 64);
+
+        let m = RawPlaintext::from(BigInt::from(10));
+        let c = Paillier::encrypt(&ek, m.clone());
+        assert_eq!(Paillier::decrypt(&dk, c), m);
+    }
+
+    #[test]
+    fn test_keypair_with_rng() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let keypair = Paillier::keypair_with_rng(512, &mut StdRng::seed_from_u64(42));
+        let same = Paillier::keypair_with_rng(512, &mut StdRng::seed_from_u64(42));
+        let other = Paillier::keypair_with_rng(512, &mut StdRng::seed_from_u64(43));
+        assert_eq!(keypair, same);
+        assert_ne!(keypair, other);
+        assert_eq!(keypair.p.bit_length(), 256);
+        assert_eq!(keypair.q.bit_length(), 256);
+    }
+
+    #[test]
+    fn test_keygen_builder() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let keypair = KeygenBuilder::new()
+            .bits(512)
+            .blum()
+            .rng(&mut rng)
+            .mr_rounds(10)
+            .generate();
+        assert_eq!(keypair.p.modulus(&BigInt::from(4)), BigInt::from(3));
+        assert_eq!(keypair.q.modulus(&BigInt::from(4)), BigInt::from(3));
+        assert_eq!(keypair.p.bit_length(), 256);
+        assert_eq!(keypair.q.bit_length(), 256);
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let same = KeygenBuilder::new()
+            .bits(512)
+            .blum()
+            .rng(&mut rng)
+            .mr_rounds(10)
+            .generate();
+        assert_eq!(keypair, same);
+
+        let keypair = KeygenBuilder::new().bits(256).safe_primes().generate();
+        let two = BigInt::from(2);
+        assert!(is_prime(&(&keypair.p - BigInt::one()).div_floor(&two)));
+        assert!(is_prime(&(&keypair.q - BigInt::one()).div_floor(&two)));
+    }
+
+    #[test]
+    fn test_modulus_predicate() {
+        let four = BigInt::from(4);
+        for _ in 0..3 {
+            let keypair = KeygenBuilder::new()
+                .bits(256)
+                .with_modulus_predicate(|n| n.modulus(&four) == BigInt::one())
+                .generate();
+            let n = &keypair.p * &keypair.q;
+            assert_eq!(n.modulus(&four), BigInt::one());
+        }
+    }
+
+    #[test]
+    fn test_key_provenance() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let (_, provenance) = KeygenBuilder::new().bits(256).generate_with_provenance();
+        assert_eq!(provenance.rng, RngSource::OsRng);
+        assert_eq!(provenance.bit_length, 256);
+        assert_eq!(provenance.mr_rounds, 5);
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let (_, provenance) = KeygenBuilder::new()
+            .bits(256)
+            .rng(&mut rng)
+            .generate_with_provenance();
+        match provenance.rng {
+            RngSource::UserSupplied(name) => assert!(name.ends_with("StdRng")),
+            RngSource::OsRng => panic!("expected user supplied rng"),
+        }
+    }
+
+    #[test]
+    fn test_keypair_blum() {
+        let keypair = Paillier::keypair_blum(512);
+        assert_eq!(keypair.p.modulus(&BigInt::from(4)), BigInt::from(3));
+        assert_eq!(keypair.q.modulus(&BigInt::from(4)), BigInt::from(3));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_keypair_blocking_task() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let keypair = runtime.block_on(Paillier::keypair_blocking_task(512));
+        let bits = (&keypair.p * &keypair.q).bit_length();
+        assert!(bits == 511 || bits == 512);
+    }
+}
@@ -0,0 +1,168 @@
+//! Catalano-Fiore transformation supporting a single multiplication of ciphertexts.
+//!
+//! A level-zero ciphertext of `m` is a pair `(m - b, Enc(b))` for random `b`. Two of these
+//! can be multiplied into a level-one ciphertext, which in turn can be added to further
+//! level-zero and level-one ciphertexts, but not multiplied again.
+//! See [Catalano-Fiore15](https://eprint.iacr.org/2014/813.pdf).
+
+use std::borrow::Borrow;
+
+use curv::arithmetic::traits::*;
+
+use crate::traits::*;
+use crate::{BigInt, DecryptionKey, EncryptionKey, Paillier, RawCiphertext, RawPlaintext};
+
+/// Fresh or added ciphertext that can take part in one multiplication.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LevelZeroCiphertext {
+    pub a: BigInt,                    // plaintext masked by b, i.e. m - b mod n
+    pub beta: RawCiphertext<'static>, // encryption of the mask b
+}
+
+/// Result of multiplying two level-zero ciphertexts.
+///
+/// Grows by one pair of ciphertexts for each multiplication that went into it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LevelOneCiphertext {
+    pub alpha: RawCiphertext<'static>, // encryption of m - sum of the b1 * b2 terms
+    pub betas: Vec<(RawCiphertext<'static>, RawCiphertext<'static>)>, // encryptions of b1 and b2
+}
+
+impl Paillier {
+    /// Encrypt plaintext `m` into a level-zero ciphertext.
+    pub fn encrypt_level_zero(ek: &EncryptionKey, m: RawPlaintext) -> LevelZeroCiphertext {
+        let b = BigInt::sample_below(&ek.n);
+        let a = BigInt::mod_sub(m.0.borrow(), &b, &ek.n);
+        let beta = Paillier::encrypt(ek, RawPlaintext::from(b));
+        LevelZeroCiphertext { a, beta }
+    }
+}
+
+impl<'c1, 'c2>
+    Add<EncryptionKey, &'c1 LevelZeroCiphertext, &'c2 LevelZeroCiphertext, LevelZeroCiphertext>
+    for Paillier
+{
+    fn add(
+        ek: &EncryptionKey,
+        c1: &'c1 LevelZeroCiphertext,
+        c2: &'c2 LevelZeroCiphertext,
+    ) -> LevelZeroCiphertext {
+        let a = BigInt::mod_add(&c1.a, &c2.a, &ek.n);
+        let beta = Paillier::add(ek, raw(&c1.beta), raw(&c2.beta));
+        LevelZeroCiphertext { a, beta }
+    }
+}
+
+impl<'c1, 'c2>
+    Mul<EncryptionKey, &'c1 LevelZeroCiphertext, &'c2 LevelZeroCiphertext, LevelOneCiphertext>
+    for Paillier
+{
+    fn mul(
+        ek: &EncryptionKey,
+        c1: &'c1 LevelZeroCiphertext,
+        c2: &'c2 LevelZeroCiphertext,
+    ) -> LevelOneCiphertext {
+        // (a1 + b1)(a2 + b2) - b1 b2 = a1 a2 + a2 b1 + a1 b2
+        let b1a2: RawCiphertext = Paillier::mul(ek, raw(&c1.beta), RawPlaintext::from(&c2.a));
+        let b2a1: RawCiphertext = Paillier::mul(ek, raw(&c2.beta), RawPlaintext::from(&c1.a));
+        let cross: RawCiphertext = Paillier::add(ek, b1a2, b2a1);
+        let a1a2 = BigInt::mod_mul(&c1.a, &c2.a, &ek.n);
+        let alpha = Paillier::add(ek, cross, RawPlaintext::from(a1a2));
+        LevelOneCiphertext {
+            alpha,
+            betas: vec![(c1.beta.clone(), c2.beta.clone())],
+        }
+    }
+}
+
+impl<'c1, 'c2>
+    Add<EncryptionKey, &'c1 LevelOneCiphertext, &'c2 LevelOneCiphertext, LevelOneCiphertext>
+    for Paillier
+{
+    fn add(
+        ek: &EncryptionKey,
+        c1: &'c1 LevelOneCiphertext,
+        c2: &'c2 LevelOneCiphertext,
+    ) -> LevelOneCiphertext {
+        let alpha = Paillier::add(ek, raw(&c1.alpha), raw(&c2.alpha));
+        let betas = c1.betas.iter().chain(&c2.betas).cloned().collect();
+        LevelOneCiphertext { alpha, betas }
+    }
+}
+
+impl<'c1, 'c2>
+    Add<EncryptionKey, &'c1 LevelOneCiphertext, &'c2 LevelZeroCiphertext, LevelOneCiphertext>
+    for Paillier
+{
+    fn add(
+        ek: &EncryptionKey,
+        c1: &'c1 LevelOneCiphertext,
+        c2: &'c2 LevelZeroCiphertext,
+    ) -> LevelOneCiphertext {
+        // Enc(b) * g^a is an encryption of a + b = m
+        let c2: RawCiphertext = Paillier::add(ek, raw(&c2.beta), RawPlaintext::from(&c2.a));
+        let alpha = Paillier::add(ek, raw(&c1.alpha), c2);
+        LevelOneCiphertext {
+            alpha,
+            betas: c1.betas.clone(),
+        }
+    }
+}
+
+impl<'c, 'm> Decrypt<DecryptionKey, &'c LevelZeroCiphertext, RawPlaintext<'m>> for Paillier {
+    fn decrypt(dk: &DecryptionKey, c: &'c LevelZeroCiphertext) -> RawPlaintext<'m> {
+        let b: BigInt = Paillier::decrypt(dk, &c.beta).into();
+        RawPlaintext::from(BigInt::mod_add(&c.a, &b, &dk.n))
+    }
+}
+
+impl<'c, 'm> Decrypt<DecryptionKey, &'c LevelOneCiphertext, RawPlaintext<'m>> for Paillier {
+    fn decrypt(dk: &DecryptionKey, c: &'c LevelOneCiphertext) -> RawPlaintext<'m> {
+        let m = c.betas.iter().fold(
+            Paillier::decrypt(dk, &c.alpha).into(),
+            |m: BigInt, (b1, b2)| {
+                let b1: BigInt = Paillier::decrypt(dk, b1).into();
+                let b2: BigInt = Paillier::decrypt(dk, b2).into();
+                BigInt::mod_add(&m, &BigInt::mod_mul(&b1, &b2, &dk.n), &dk.n)
+            },
+        );
+        RawPlaintext::from(m)
+    }
+}
+
+fn raw<'c>(c: &'c RawCiphertext) -> RawCiphertext<'c> {
+    RawCiphertext::from(&*c.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encrypt(ek: &EncryptionKey, m: u32) -> LevelZeroCiphertext {
+        Paillier::encrypt_level_zero(ek, RawPlaintext::from(BigInt::from(m)))
+    }
+
+    #[test]
+    fn test_level_zero() {
+        let (ek, dk) = Paillier::keypair_unsafe_small(256).keys();
+
+        let c = Paillier::add(&ek, &encrypt(&ek, 3), &encrypt(&ek, 4));
+        let m = Paillier::decrypt(&dk, &c);
+        assert_eq!(m, BigInt::from(7).into());
+    }
+
+    #[test]
+    fn test_multiply_then_add() {
+        let (ek, dk) = Paillier::keypair_unsafe_small(256).keys();
+
+        let product: LevelOneCiphertext = Paillier::mul(&ek, &encrypt(&ek, 3), &encrypt(&ek, 4));
+        let c: LevelOneCiphertext = Paillier::add(&ek, &product, &encrypt(&ek, 5));
+        let m = Paillier::decrypt(&dk, &c);
+        assert_eq!(m, BigInt::from(17).into());
+
+        let c: LevelOneCiphertext = Paillier::add(&ek, &c, &product);
+        assert_eq!(c.betas.len(), 2);
+        let m = Paillier::decrypt(&dk, &c);
+        assert_eq!(m, BigInt::from(29).into());
+    }
+}
@@ -2,22 +2,126 @@ use std::borrow::Cow;
 
 use serde::{Deserialize, Serialize};
 
+pub mod accumulator;
+pub mod compact;
+#[cfg(feature = "num-bigint")]
+pub mod convert;
 pub mod core;
 pub mod encoding;
+pub mod error;
 pub mod keygen;
+pub mod leveled;
+pub mod noise;
 pub mod serialize;
 pub mod traits;
+pub mod two_party;
 
 pub use crate::core::*;
+pub use accumulator::*;
 pub use encoding::*;
+pub use error::Error;
 pub use keygen::*;
 pub use traits::*;
 
 pub use curv::arithmetic::BigInt;
 
+/// Name of the arbitrary precision library selected through the crate features.
+///
+/// Reads `"unspecified"` if the library was instead selected through the features of `curv`.
+#[cfg(feature = "backend-gmp")]
+pub const BACKEND: &str = "gmp";
+#[cfg(all(feature = "backend-num-bigint", not(feature = "backend-gmp")))]
+pub const BACKEND: &str = "num-bigint";
+#[cfg(not(any(feature = "backend-gmp", feature = "backend-num-bigint")))]
+pub const BACKEND: &str = "unspecified";
+
 /// Main struct onto which most operations are added.
 pub struct Paillier;
 
+/// Description of the arithmetic backend this build uses.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BackendInfo {
+    /// Backend name, see `BACKEND`.
+    pub name: &'static str,
+    /// Path of the big integer type in use, e.g. `curv::arithmetic::big_gmp::BigInt`.
+    pub bigint: &'static str,
+    /// Version of this crate. The version of the backend library itself is not reported.
+    pub crate_version: &'static str,
+}
+
+impl Paillier {
+    /// Report the arithmetic backend, e.g. for inclusion in bug reports and benchmarks.
+    pub fn backend_info() -> BackendInfo {
+        BackendInfo {
+            name: BACKEND,
+            bigint: std::any::type_name::<BigInt>(),
+            crate_version: env!("CARGO_PKG_VERSION"),
+        }
+    }
+
+    /// Check the arithmetic backend against known answers, e.g. on application startup.
+    ///
+    /// Covers the operations used by encryption, decryption and opening, and a full
+    /// round trip under a tiny fixed key; returns a description of the first failure.
+    pub fn self_test() -> Result<(), String> {
+        use curv::arithmetic::traits::*;
+
+        fn check(name: &str, actual: BigInt, expected: &str) -> Result<(), String> {
+            let expected = BigInt::from_str_radix(expected, 10).map_err(|_| name.to_string())?;
+            if actual == expected {
+                Ok(())
+            } else {
+                Err(format!(
+                    "{}: expected {}, got {}",
+                    name,
+                    expected.to_str_radix(10),
+                    actual.to_str_radix(10)
+                ))
+            }
+        }
+
+        let m = (BigInt::one() << 127) - BigInt::one();
+        let e = (BigInt::one() << 100) + BigInt::from(7);
+        check(
+            "mod_pow",
+            BigInt::mod_pow(&BigInt::from(3), &e, &m),
+            "35918501684820074657246006075159498392",
+        )?;
+        let x = BigInt::from_str_radix("123456789123456789", 10).unwrap();
+        check(
+            "mod_inv",
+            BigInt::mod_inv(&x, &m).ok_or("mod_inv: no inverse")?,
+            "33785341412481728823582264134804948706",
+        )?;
+        let a = (BigInt::one() << 64) * BigInt::from(3 * 7 * 11);
+        let b = (BigInt::one() << 40) * BigInt::from(7 * 13 * 11);
+        check("gcd", BigInt::gcd(&a, &b), "84662395338752")?;
+        check(
+            "bytes",
+            BigInt::from_bytes(&m.to_bytes()),
+            "170141183460469231731687303715884105727",
+        )?;
+
+        let (ek, dk) = Keypair::from((&BigInt::from(11), &BigInt::from(13))).keys();
+        let r = Randomness::from(BigInt::from(5));
+        let c: RawCiphertext =
+            Paillier::encrypt_with_chosen_randomness(&ek, RawPlaintext::from(BigInt::from(42)), &r);
+        check("encrypt", c.clone().into(), "1841")?;
+        check(
+            "decrypt",
+            Paillier::decrypt_with_threshold(&dk, &c, usize::MAX).into(),
+            "42",
+        )?;
+        check(
+            "decrypt_crt",
+            Paillier::decrypt_with_threshold(&dk, &c, 0).into(),
+            "42",
+        )?;
+        let (_, r_opened) = Paillier::open(&dk, &c);
+        check("open", r_opened.0, "5")
+    }
+}
+
 /// Keypair from which encryption and decryption keys can be derived.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Keypair {
@@ -31,9 +135,9 @@ pub struct Keypair {
 /// Public encryption key with no precomputed values.
 ///
 /// Used e.g. for serialization of `EncryptionKey`.
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MinimalEncryptionKey {
-    #[serde(with = "crate::serialize::bigint")]
+    #[serde(with = "crate::serialize::modulus")]
     pub n: BigInt,
 }
 
@@ -50,17 +154,40 @@ pub struct MinimalDecryptionKey {
 }
 
 /// Public encryption key.
-#[derive(Clone, Debug, PartialEq)]
+///
+/// Holds no precomputed tables or interior mutability, so a single key may be shared
+/// between threads, e.g. behind an `Arc`, and used for concurrent encryption.
+///
+/// Implements `Hash` based on the modulus; decryption keys deliberately do not.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct EncryptionKey {
-    pub n: BigInt,  // the modulus
-    pub nn: BigInt, // the modulus squared
+    pub n: BigInt,               // the modulus
+    pub nn: BigInt,              // the modulus squared
+    pub(crate) fingerprint: u64, // tag of the modulus, see `fingerprint`
 }
 
 /// Private decryption key.
+///
+/// Holds all values needed for decryption that only depend on the primes, computed
+/// once when the key is constructed from a `MinimalDecryptionKey` or `Keypair`.
 #[derive(Clone, Debug, PartialEq)]
 pub struct DecryptionKey {
-    pub p: BigInt, // first prime
-    pub q: BigInt, // second prime
+    pub p: BigInt,         // first prime
+    pub q: BigInt,         // second prime
+    pub pp: BigInt,        // first prime squared
+    pub qq: BigInt,        // second prime squared
+    pub n: BigInt,         // the modulus
+    pub nn: BigInt,        // the modulus squared
+    pub pinv: BigInt,      // first prime inverted modulo second prime
+    pub ppinv: BigInt,     // first prime squared inverted modulo second prime squared
+    pub pminusone: BigInt, // first prime minus one
+    pub qminusone: BigInt, // second prime minus one
+    pub hp: BigInt,        // CRT decryption constant for the first prime
+    pub hq: BigInt,        // CRT decryption constant for the second prime
+    pub lambda: BigInt,    // Carmichael function of the modulus, lcm(p-1, q-1)
+    pub mu: BigInt,        // lambda inverted modulo the modulus
+    pub dp: BigInt,        // modulus inverted modulo phi, reduced modulo first prime minus one
+    pub dq: BigInt,        // modulus inverted modulo phi, reduced modulo second prime minus one
 }
 
 /// Unencrypted message without type information.
@@ -72,5 +199,42 @@ pub struct RawPlaintext<'b>(pub Cow<'b, BigInt>);
 /// Encrypted message without type information.
 ///
 /// Used mostly for internal purposes and advanced use-cases.
+/// Equality is only meaningful between canonical ciphertexts, see `canonicalize`.
 #[derive(Clone, Debug, PartialEq)]
 pub struct RawCiphertext<'b>(pub Cow<'b, BigInt>);
+
+/// Value paired with the encryption key it belongs to.
+///
+/// Allows homomorphic operations to be written using the standard operators,
+/// e.g. `&a + &b` for ciphertexts `a` and `b` and `&a * &m` for plaintext `m`.
+///
+/// The value is tagged with the `fingerprint` of the key when wrapped. Operators panic
+/// if operands carry different tags or a tag no longer matches `ek`; `checked_add` and
+/// `checked_mul` return `Error::ModulusMismatch` instead. The untagged operations on
+/// `Paillier` cannot detect mixed keys.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WithKey<'k, T> {
+    pub ek: &'k EncryptionKey,
+    pub value: T,
+    pub(crate) fingerprint: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_info() {
+        assert!(!BACKEND.is_empty());
+
+        let info = Paillier::backend_info();
+        assert_eq!(info.name, BACKEND);
+        assert!(info.bigint.ends_with("BigInt"));
+        assert_eq!(info.crate_version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn test_self_test() {
+        assert_eq!(Paillier::self_test(), Ok(()));
+    }
+}
@@ -0,0 +1,148 @@
+//! Encrypted noise for differentially private aggregation.
+
+use curv::arithmetic::traits::*;
+use rand::{CryptoRng, Rng, RngCore};
+
+use crate::traits::*;
+use crate::{BigInt, EncryptionKey, Error, Paillier, Randomness, RawCiphertext, RawPlaintext};
+
+/// Distribution of integer noise.
+///
+/// Prefer the validating constructors, e.g. `discrete_laplace`, when parameters come from
+/// configuration; sampling from a directly built distribution with invalid parameters panics.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NoiseDistribution {
+    /// Discrete Laplace distribution with `P(x) ~ exp(-|x| / scale)`.
+    DiscreteLaplace { scale: f64 },
+}
+
+impl NoiseDistribution {
+    /// Discrete Laplace distribution, failing with `Error::InvalidParameter` unless `scale`
+    /// is positive and finite.
+    pub fn discrete_laplace(scale: f64) -> Result<NoiseDistribution, Error> {
+        if !(scale > 0.0 && scale.is_finite()) {
+            return Err(Error::InvalidParameter);
+        }
+        Ok(NoiseDistribution::DiscreteLaplace { scale })
+    }
+
+    /// Draw a noise value using `rng`.
+    ///
+    /// # Panics
+    ///
+    /// If the scale of `DiscreteLaplace` is not positive and finite, which `discrete_laplace`
+    /// rules out.
+    pub fn sample<R: RngCore + ?Sized>(&self, rng: &mut R) -> i64 {
+        match *self {
+            NoiseDistribution::DiscreteLaplace { scale } => {
+                // difference of two geometric variables with success probability 1 - exp(-1/scale)
+                assert!(
+                    scale > 0.0 && scale.is_finite(),
+                    "scale must be positive and finite"
+                );
+                let alpha = (-1.0 / scale).exp();
+                let mut geometric = || {
+                    let u: f64 = 1.0 - rng.gen::<f64>(); // in (0, 1]
+                    (u.ln() / alpha.ln()).floor() as i64
+                };
+                geometric() - geometric()
+            }
+        }
+    }
+}
+
+impl Paillier {
+    /// Draw noise from `distribution` and encrypt it, with all randomness taken from `rng`.
+    ///
+    /// Returns the ciphertext together with the noise value for privacy accounting;
+    /// negative noise is encrypted as its residue modulo `n`.
+    ///
+    /// # Panics
+    ///
+    /// Under the same conditions as `NoiseDistribution::sample`.
+    pub fn encrypt_noise<R: RngCore + CryptoRng>(
+        ek: &EncryptionKey,
+        distribution: &NoiseDistribution,
+        rng: &mut R,
+    ) -> (RawCiphertext<'static>, i64) {
+        let noise = distribution.sample(rng);
+        let magnitude = BigInt::from(noise.unsigned_abs());
+        let m = if noise < 0 {
+            BigInt::mod_sub(&BigInt::zero(), &magnitude, &ek.n)
+        } else {
+            magnitude
+        };
+        let r = Randomness::sample_with_rng(ek, rng);
+        let c = Paillier::encrypt_with_chosen_randomness(ek, RawPlaintext::from(m), &r);
+        (c, noise)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_discrete_laplace_moments() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let scale = 2.0;
+        let distribution = NoiseDistribution::discrete_laplace(scale).unwrap();
+
+        let draws = 20_000;
+        let samples: Vec<f64> = (0..draws)
+            .map(|_| distribution.sample(&mut rng) as f64)
+            .collect();
+        let mean = samples.iter().sum::<f64>() / draws as f64;
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / draws as f64;
+
+        // variance of the discrete Laplace distribution is 2 a / (1 - a)^2 for a = exp(-1/scale)
+        let alpha = (-1.0f64 / scale).exp();
+        let expected_variance = 2.0 * alpha / (1.0 - alpha).powi(2);
+        assert!(mean.abs() < 0.15, "mean {}", mean);
+        assert!(
+            (variance - expected_variance).abs() < 0.05 * expected_variance,
+            "variance {} expected {}",
+            variance,
+            expected_variance
+        );
+    }
+
+    #[test]
+    fn test_encrypt_noise() {
+        let (ek, dk) = Paillier::keypair_unsafe_small(128).keys();
+        let mut rng = StdRng::seed_from_u64(42);
+        let distribution = NoiseDistribution::discrete_laplace(10.0).unwrap();
+
+        for _ in 0..20 {
+            let (c, noise) = Paillier::encrypt_noise(&ek, &distribution, &mut rng);
+            let m: BigInt = Paillier::decrypt(&dk, c).into();
+            let expected = if noise < 0 {
+                &ek.n - BigInt::from(noise.unsigned_abs())
+            } else {
+                BigInt::from(noise as u64)
+            };
+            assert_eq!(m, expected);
+        }
+    }
+
+    #[test]
+    fn test_invalid_scale() {
+        for scale in &[0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert_eq!(
+                NoiseDistribution::discrete_laplace(*scale),
+                Err(Error::InvalidParameter)
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "scale must be positive and finite")]
+    fn test_sample_invalid_scale() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let distribution = NoiseDistribution::DiscreteLaplace { scale: f64::NAN };
+        distribution.sample(&mut rng);
+    }
+}
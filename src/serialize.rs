@@ -30,6 +30,26 @@ pub mod bigint {
     }
 }
 
+/// Like `bigint`, but rejecting values that cannot be a modulus, i.e. not above one.
+pub mod modulus {
+    use curv::arithmetic::traits::*;
+    use curv::arithmetic::BigInt;
+
+    use serde::{de, ser};
+
+    pub fn serialize<S: ser::Serializer>(x: &BigInt, serializer: S) -> Result<S::Ok, S::Error> {
+        super::bigint::serialize(x, serializer)
+    }
+
+    pub fn deserialize<'de, D: de::Deserializer<'de>>(deserializer: D) -> Result<BigInt, D::Error> {
+        let n = super::bigint::deserialize(deserializer)?;
+        if n <= BigInt::one() {
+            return Err(de::Error::custom("modulus must be greater than one"));
+        }
+        Ok(n)
+    }
+}
+
 pub mod vecbigint {
     use std::fmt;
 
@@ -66,7 +86,7 @@ pub mod vecbigint {
             {
                 let mut values: Vec<BigInt> = Vec::new();
                 while let Some(value) = seq.next_element::<String>()? {
-                    values.push(BigInt::from_str_radix(&value, 10).unwrap());
+                    values.push(BigInt::from_str_radix(&value, 10).map_err(de::Error::custom)?);
                 }
 
                 Ok(values)
@@ -1,6 +1,9 @@
 //! Abstract operations exposed by the library.
 
 /// Secure generation of fresh key pairs.
+///
+/// Key generation only samples primes; no proof of correct key generation is computed,
+/// so the cost measured by the `keygen` benchmark is prime sampling alone.
 pub trait KeyGeneration<KP> {
     /// Generate fresh key pair with currently recommended security level (2048 bit modulus).
     fn keypair() -> KP {
@@ -58,6 +61,13 @@ pub trait Mul<EK, CT1, PT2, CT> {
     fn mul(ek: &EK, c1: CT1, m2: PT2) -> CT;
 }
 
+/// Negation of ciphertext.
+pub trait Neg<EK, CT1, CT> {
+    /// Homomorphically negate ciphertext `c` to obtain a ciphertext containing the
+    /// additive inverse of the underlying plaintext, reduced modulus `n` from `ek`.
+    fn neg(ek: &EK, c: CT1) -> CT;
+}
+
 /// Rerandomisation of ciphertext.
 pub trait Rerandomize<EK, CT1, CT> {
     /// Rerandomise ciphertext `c` to hide any history of which homomorphic operations were
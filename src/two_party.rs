@@ -0,0 +1,113 @@
+//! Decryption jointly by two parties each holding an additive share of the secret key.
+//!
+//! The decryption exponent `d`, with `d = 0 mod lambda` and `d = 1 mod n`, is split into
+//! `d = d1 + d2 mod n * lambda`. Each party raises the ciphertext to its own share, and
+//! only combining both partial decryptions gives `c^d = 1 + m * n mod n^2`.
+
+use serde::{Deserialize, Serialize};
+
+use curv::arithmetic::traits::*;
+
+use crate::traits::*;
+use crate::{BigInt, DecryptionKey, EncryptionKey, Paillier, RawCiphertext, RawPlaintext};
+
+/// Share of the decryption exponent held by one of the two parties, to be kept secret.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KeyShare {
+    #[serde(with = "crate::serialize::bigint")]
+    pub n: BigInt,
+
+    #[serde(with = "crate::serialize::bigint")]
+    pub share: BigInt,
+}
+
+/// Result of one party applying its share to a ciphertext.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PartialDecryption(#[serde(with = "crate::serialize::bigint")] pub BigInt);
+
+/// Generate fresh key with the decryption key shared between two parties.
+///
+/// This is a trusted dealer setup: the primes exist in memory while the shares are
+/// computed and should be erased afterwards.
+pub fn keygen_2p(bit_length: usize) -> (EncryptionKey, KeyShare, KeyShare) {
+    let (ek, dk) = Paillier::keypair_with_modulus_size(bit_length).keys();
+    let (share1, share2) = split(&dk);
+    (ek, share1, share2)
+}
+
+/// Split existing decryption key into two shares.
+pub fn split(dk: &DecryptionKey) -> (KeyShare, KeyShare) {
+    let lambda = dk.lambda();
+    let order = &dk.n * lambda;
+    let d = lambda * dk.mu();
+
+    let d1 = BigInt::sample_below(&order);
+    let d2 = BigInt::mod_sub(&d, &d1, &order);
+    let share = |d| KeyShare {
+        n: dk.n.clone(),
+        share: d,
+    };
+    (share(d1), share(d2))
+}
+
+/// Apply key share to ciphertext `c`.
+pub fn partial_decrypt(share: &KeyShare, c: &RawCiphertext) -> PartialDecryption {
+    let nn = &share.n * &share.n;
+    PartialDecryption(BigInt::mod_pow(&c.0, &share.share, &nn))
+}
+
+/// Recover the plaintext from the partial decryptions of both parties.
+pub fn combine(
+    ek: &EncryptionKey,
+    pd1: &PartialDecryption,
+    pd2: &PartialDecryption,
+) -> RawPlaintext<'static> {
+    let x = BigInt::mod_mul(&pd1.0, &pd2.0, &ek.nn);
+    RawPlaintext::from((x - BigInt::one()) / &ek.n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_party_decryption() {
+        let (ek, dk) = Paillier::keypair_unsafe_small(256).keys();
+        let (share1, share2) = split(&dk);
+
+        let m = RawPlaintext::from(BigInt::from(10));
+        let c = Paillier::encrypt(&ek, m.clone());
+        let pd1 = partial_decrypt(&share1, &c);
+        let pd2 = partial_decrypt(&share2, &c);
+        assert_eq!(combine(&ek, &pd1, &pd2), m);
+        assert_eq!(combine(&ek, &pd2, &pd1), m);
+    }
+
+    #[test]
+    fn test_both_shares_needed() {
+        let (ek, dk) = Paillier::keypair_unsafe_small(256).keys();
+        let (share1, share2) = split(&dk);
+
+        let m = RawPlaintext::from(BigInt::from(10));
+        let c = Paillier::encrypt(&ek, m.clone());
+        let pd1 = partial_decrypt(&share1, &c);
+        let pd2 = partial_decrypt(&share2, &c);
+        let one = PartialDecryption(BigInt::one());
+        assert_ne!(combine(&ek, &pd1, &one), m);
+        assert_ne!(combine(&ek, &one, &pd2), m);
+        assert_ne!(combine(&ek, &pd1, &pd1), m);
+    }
+
+    #[test]
+    fn test_keygen_2p() {
+        let (ek, share1, share2) = keygen_2p(512);
+        assert_eq!(share1.n, ek.n);
+        assert_eq!(share2.n, ek.n);
+
+        let m = RawPlaintext::from(BigInt::from(10));
+        let c = Paillier::encrypt(&ek, m.clone());
+        let pd1 = partial_decrypt(&share1, &c);
+        let pd2 = partial_decrypt(&share2, &c);
+        assert_eq!(combine(&ek, &pd1, &pd2), m);
+    }
+}